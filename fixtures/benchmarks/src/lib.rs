@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use std::time::{Duration, Instant};
+use std::time::Instant;
 
 pub struct TestData {
     pub foo: String,
@@ -13,6 +13,7 @@ pub enum TestCase {
     Function,
     VoidReturn,
     NoArgsVoidReturn,
+    NestedStrings,
 }
 
 pub trait TestCallbackInterface: Send + Sync {
@@ -35,6 +36,14 @@ pub fn test_no_args_void_return() {
     // Intentionally does nothing - testing minimal call overhead
 }
 
+pub async fn test_async_function(_a: i32, _b: i32, data: TestData) -> String {
+    data.bar
+}
+
+pub fn test_nested_strings(data: Vec<TestData>) -> u32 {
+    data.len() as u32
+}
+
 pub fn run_benchmarks(language: String, cb: Box<dyn TestCallbackInterface>) {
     println!("Running benchmarks for {language}");
 
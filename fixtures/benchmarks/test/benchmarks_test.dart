@@ -1,3 +1,8 @@
+import 'dart:async';
+import 'dart:ffi';
+import 'dart:io';
+import 'dart:typed_data';
+
 import 'package:test/test.dart';
 import '../benchmarks.dart';
 
@@ -46,6 +51,15 @@ class DartTestCallbackInterface implements TestCallbackInterface {
           testNoArgsVoidReturn();
         }
         break;
+      case TestCase.nestedStrings:
+        final data = List.generate(
+          32,
+          (i) => TestData(foo: 'SomeStringData $i', bar: 'Ünïcödé data — $i 🚀'),
+        );
+        for (int i = 0; i < count; i++) {
+          testNestedStrings(data);
+        }
+        break;
     }
 
     stopwatch.stop();
@@ -53,6 +67,140 @@ class DartTestCallbackInterface implements TestCallbackInterface {
   }
 }
 
+// The library functions the baselines below call directly, looked up like the bindings do
+class BenchmarksLib {
+  static final DynamicLibrary _dylib = _open();
+
+  static DynamicLibrary _open() {
+    if (Platform.isMacOS) return DynamicLibrary.open('libbenchmarks.dylib');
+    if (Platform.isWindows) return DynamicLibrary.open('benchmarks.dll');
+    return DynamicLibrary.open('${Directory.current.path}/libbenchmarks.so');
+  }
+
+  static final testNestedStrings = _dylib.lookupFunction<
+      Uint32 Function(RustBuffer, Pointer<RustCallStatus>),
+      int Function(RustBuffer, Pointer<RustCallStatus>)>(
+    'uniffi_benchmarks_fn_func_test_nested_strings',
+  );
+
+  static final testAsyncFunction = _dylib.lookupFunction<
+      Pointer<Void> Function(Int32, Int32, RustBuffer),
+      Pointer<Void> Function(int, int, RustBuffer)>(
+    'uniffi_benchmarks_fn_func_test_async_function',
+  );
+
+  static final pollRustBuffer = _dylib.lookupFunction<
+      Void Function(Pointer<Void>,
+          Pointer<NativeFunction<UniffiRustFutureContinuationCallback>>, Pointer<Void>),
+      void Function(Pointer<Void>,
+          Pointer<NativeFunction<UniffiRustFutureContinuationCallback>>, Pointer<Void>)>(
+    'ffi_benchmarks_rust_future_poll_rust_buffer',
+  );
+
+  static final completeRustBuffer = _dylib.lookupFunction<
+      RustBuffer Function(Pointer<Void>, Pointer<RustCallStatus>),
+      RustBuffer Function(Pointer<Void>, Pointer<RustCallStatus>)>(
+    'ffi_benchmarks_rust_future_complete_rust_buffer',
+  );
+
+  static final freeRustBuffer = _dylib.lookupFunction<Void Function(Pointer<Void>),
+      void Function(Pointer<Void>)>(
+    'ffi_benchmarks_rust_future_free_rust_buffer',
+  );
+}
+
+// Baseline: the hand-written UTF-8 encoder strings were written with before `utf8.encode`
+int legacyWriteUtf8(String value, Uint8List buf, int offset) {
+  var pos = offset;
+  for (var i = 0; i < value.length; i++) {
+    var unit = value.codeUnitAt(i);
+    if (unit < 0x80) {
+      buf[pos++] = unit;
+    } else if (unit < 0x800) {
+      buf[pos++] = 0xC0 | (unit >> 6);
+      buf[pos++] = 0x80 | (unit & 0x3F);
+    } else if ((unit & 0xFC00) == 0xD800 &&
+        i + 1 < value.length &&
+        (value.codeUnitAt(i + 1) & 0xFC00) == 0xDC00) {
+      final rune = 0x10000 + ((unit & 0x3FF) << 10) + (value.codeUnitAt(++i) & 0x3FF);
+      buf[pos++] = 0xF0 | (rune >> 18);
+      buf[pos++] = 0x80 | ((rune >> 12) & 0x3F);
+      buf[pos++] = 0x80 | ((rune >> 6) & 0x3F);
+      buf[pos++] = 0x80 | (rune & 0x3F);
+    } else {
+      if ((unit & 0xF800) == 0xD800) {
+        unit = 0xFFFD;
+      }
+      buf[pos++] = 0xE0 | (unit >> 12);
+      buf[pos++] = 0x80 | ((unit >> 6) & 0x3F);
+      buf[pos++] = 0x80 | (unit & 0x3F);
+    }
+  }
+  return pos - offset;
+}
+
+int legacyWriteString(String value, Uint8List buf) {
+  final length = legacyWriteUtf8(value, buf, 4);
+  buf.buffer.asByteData(buf.offsetInBytes).setInt32(0, length);
+  return length + 4;
+}
+
+// Writes `data` in the same layout as `FfiConverterSequenceTestData.write`
+int legacyWriteTestDataList(List<TestData> data, Uint8List buf) {
+  buf.buffer.asByteData(buf.offsetInBytes).setInt32(0, data.length);
+  var offset = buf.offsetInBytes + 4;
+  for (final item in data) {
+    offset += legacyWriteString(item.foo, Uint8List.view(buf.buffer, offset));
+    offset += legacyWriteString(item.bar, Uint8List.view(buf.buffer, offset));
+  }
+  return offset - buf.offsetInBytes;
+}
+
+int legacyTestNestedStrings(List<TestData> data) {
+  return rustCall((status) => BenchmarksLib.testNestedStrings(
+        uniffiLowerIntoRustBuffer(
+          FfiConverterSequenceTestData.allocationSize(data),
+          (buf) => legacyWriteTestDataList(data, buf),
+        ),
+        status,
+      ));
+}
+
+// Baseline: polls the same Rust future as `testAsyncFunction`, with the continuation callback
+// async calls created and closed for every call before they shared one
+Future<String> legacyTestAsyncFunction(int a, int b, TestData data) async {
+  final rustFuture = BenchmarksLib.testAsyncFunction(a, b, FfiConverterTestData.lower(data));
+  final completer = Completer<int>();
+
+  late final NativeCallable<UniffiRustFutureContinuationCallback> callback;
+
+  void poll() {
+    BenchmarksLib.pollRustBuffer(rustFuture, callback.nativeFunction, Pointer<Void>.fromAddress(0));
+  }
+
+  void onResponse(int _, int pollResult) {
+    if (pollResult == UNIFFI_RUST_FUTURE_POLL_READY) {
+      completer.complete(pollResult);
+    } else {
+      poll();
+    }
+  }
+
+  callback = NativeCallable<UniffiRustFutureContinuationCallback>.listener(onResponse);
+
+  try {
+    poll();
+    await completer.future;
+    return rustCall((status) =>
+        FfiConverterString.lift(BenchmarksLib.completeRustBuffer(rustFuture, status)));
+  } finally {
+    callback.close();
+    BenchmarksLib.freeRustBuffer(rustFuture);
+  }
+}
+
+int nsPerCall(Stopwatch stopwatch, int count) => stopwatch.elapsedMicroseconds * 1000 ~/ count;
+
 void main() {
   group('Benchmarks', () {
     test('basic function benchmarking', () {
@@ -109,6 +257,108 @@ void main() {
       expect(noArgsTime, greaterThan(0));
     });
 
+    test('nested string serialization', () {
+      final data = [
+        TestData(foo: 'ascii', bar: 'Ünïcödé — 🚀'),
+        TestData(foo: '', bar: '日本語'),
+      ];
+      expect(testNestedStrings(data), equals(2));
+
+      final callback = DartTestCallbackInterface();
+      callback.runTest(TestCase.nestedStrings, 100); // warm up
+      final elapsed = callback.runTest(TestCase.nestedStrings, 10000);
+      print('Dart-functions-nested-strings: ${elapsed ~/ 10000}ns per call');
+      expect(elapsed, greaterThan(0));
+    });
+
+    test('nested string serialization against the hand-written encoder', () {
+      final data = List.generate(
+        32,
+        (i) => TestData(foo: 'SomeStringData $i', bar: 'Ünïcödé data — $i 🚀 \u{D800}'),
+      );
+
+      // Both encoders produce the same bytes
+      final size = FfiConverterSequenceTestData.allocationSize(data);
+      final encoded = Uint8List(size);
+      final legacyEncoded = Uint8List(size);
+      expect(FfiConverterSequenceTestData.write(data, encoded), equals(size));
+      expect(legacyWriteTestDataList(data, legacyEncoded), equals(size));
+      expect(legacyEncoded, equals(encoded));
+      expect(legacyTestNestedStrings(data), equals(32));
+
+      const count = 10000;
+      for (int i = 0; i < 100; i++) {
+        testNestedStrings(data); // warm up
+        legacyTestNestedStrings(data);
+      }
+
+      final current = Stopwatch()..start();
+      for (int i = 0; i < count; i++) {
+        testNestedStrings(data);
+      }
+      current.stop();
+
+      final legacy = Stopwatch()..start();
+      for (int i = 0; i < count; i++) {
+        legacyTestNestedStrings(data);
+      }
+      legacy.stop();
+
+      print('Dart-functions-nested-strings-utf8-encode: ${nsPerCall(current, count)}ns per call');
+      print('Dart-functions-nested-strings-hand-written: ${nsPerCall(legacy, count)}ns per call');
+    });
+
+    test('async function benchmarking', () async {
+      final data = TestData(foo: 'SomeStringData', bar: 'SomeMoreStringData');
+      expect(await testAsyncFunction(10, 100, data), equals('SomeMoreStringData'));
+
+      const count = 10000;
+      for (int i = 0; i < 100; i++) {
+        await testAsyncFunction(10, 100, data); // warm up
+      }
+
+      final sequential = Stopwatch()..start();
+      for (int i = 0; i < count; i++) {
+        await testAsyncFunction(10, 100, data);
+      }
+      sequential.stop();
+      print('Dart-functions-async-sequential: ${nsPerCall(sequential, count)}ns per call');
+
+      final concurrent = Stopwatch()..start();
+      await Future.wait(List.generate(count, (_) => testAsyncFunction(10, 100, data)));
+      concurrent.stop();
+      print('Dart-functions-async-concurrent: ${nsPerCall(concurrent, count)}ns per call');
+    });
+
+    test('async calls against a continuation callback per call', () async {
+      final data = TestData(foo: 'SomeStringData', bar: 'SomeMoreStringData');
+      expect(await legacyTestAsyncFunction(10, 100, data), equals('SomeMoreStringData'));
+
+      const count = 10000;
+      for (int i = 0; i < 100; i++) {
+        await testAsyncFunction(10, 100, data); // warm up
+        await legacyTestAsyncFunction(10, 100, data);
+      }
+
+      final shared = Stopwatch()..start();
+      for (int i = 0; i < count; i++) {
+        await testAsyncFunction(10, 100, data);
+      }
+      shared.stop();
+
+      final perCall = Stopwatch()..start();
+      for (int i = 0; i < count; i++) {
+        await legacyTestAsyncFunction(10, 100, data);
+      }
+      perCall.stop();
+
+      final sharedNs = nsPerCall(shared, count);
+      final perCallNs = nsPerCall(perCall, count);
+      print('Dart-functions-async-shared-callback: ${sharedNs}ns per call');
+      print('Dart-functions-async-callback-per-call: ${perCallNs}ns per call');
+      print('Dart-functions-async-shared-callback-saves: ${perCallNs - sharedNs}ns per call');
+    });
+
     test('full benchmark suite', () {
       // This test will fail until callback interface support is implemented
       // Expected: Should be able to run the full benchmark suite
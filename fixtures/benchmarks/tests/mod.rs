@@ -1,7 +1,6 @@
-#[cfg(test)]
-mod tests {
-    #[test]
-    fn test_benchmarks() {
-        uniffi_dart::testing::run_test("benchmarks", None).unwrap();
-    }
+use anyhow::Result;
+
+#[test]
+fn benchmarks() -> Result<()> {
+    uniffi_dart::testing::run_test("benchmarks", "src/api.udl", None)
 }
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+pub struct Counters {
+    pub total: u64,
+    pub history: Vec<u64>,
+    pub by_name: HashMap<String, u64>,
+    pub limit: Option<u64>,
+}
+
+pub trait U64Transformer: Send + Sync {
+    fn transform(&self, value: u64) -> u64;
+}
+
+fn max_u64() -> u64 {
+    u64::MAX
+}
+
+fn echo_u64(value: u64) -> u64 {
+    value
+}
+
+fn wrapping_add_u64(a: u64, b: u64) -> u64 {
+    a.wrapping_add(b)
+}
+
+fn echo_counters(counters: Counters) -> Counters {
+    counters
+}
+
+fn echo_u64s(values: Vec<u64>) -> Vec<u64> {
+    values
+}
+
+fn echo_u64_map(values: HashMap<String, u64>) -> HashMap<String, u64> {
+    values
+}
+
+fn echo_optional_u64(value: Option<u64>) -> Option<u64> {
+    value
+}
+
+fn transform_with(transformer: Box<dyn U64Transformer>, value: u64) -> u64 {
+    transformer.transform(value)
+}
+
+// `BigInt` has no constant values, so these defaults are filled in when they are used
+#[derive(uniffi::Record)]
+pub struct Quota {
+    #[uniffi(default = 18446744073709551615)]
+    pub limit: u64,
+    #[uniffi(default)]
+    pub used: u64,
+    #[uniffi(default = 10)]
+    pub step: u64,
+}
+
+#[uniffi::export]
+pub fn echo_quota(quota: Quota) -> Quota {
+    quota
+}
+
+#[uniffi::export(default(step = 1))]
+pub fn advance(value: u64, step: u64) -> u64 {
+    value.wrapping_add(step)
+}
+
+// A custom type configured to stay a Dart `int`
+pub struct Nanos(pub u64);
+uniffi::custom_newtype!(Nanos, u64);
+
+#[uniffi::export]
+pub fn echo_nanos(value: Nanos) -> Nanos {
+    value
+}
+
+#[uniffi::export]
+pub fn nanos_is_max(value: Nanos) -> bool {
+    value.0 == u64::MAX
+}
+
+uniffi::include_scaffolding!("api");
@@ -0,0 +1,76 @@
+import 'package:test/test.dart';
+import '../bigint_types.dart';
+
+final u64Max = (BigInt.one << 64) - BigInt.one;
+
+void main() {
+  group('BigInt u64', () {
+    test('values above i64::MAX keep their value', () {
+      expect(maxU64(), equals(u64Max));
+      expect(echoU64(u64Max), equals(u64Max));
+      expect(echoU64(BigInt.zero), equals(BigInt.zero));
+      expect(echoU64(BigInt.parse('9223372036854775808')),
+          equals(BigInt.parse('9223372036854775808')));
+    });
+
+    test('arithmetic happens on the Rust side', () {
+      expect(wrappingAddU64(u64Max, BigInt.two), equals(BigInt.one));
+      expect(wrappingAddU64(BigInt.from(40), BigInt.two), equals(BigInt.from(42)));
+    });
+
+    test('out of range values are rejected', () {
+      expect(() => echoU64(BigInt.from(-1)), throwsArgumentError);
+      expect(() => echoU64(u64Max + BigInt.one), throwsArgumentError);
+      expect(() => echoU64s([BigInt.one, BigInt.from(-1)]), throwsArgumentError);
+    });
+
+    test('records, sequences, maps and optionals', () {
+      final counters = Counters(
+        u64Max,
+        [BigInt.zero, u64Max],
+        {'max': u64Max, 'one': BigInt.one},
+        u64Max - BigInt.one,
+      );
+      expect(echoCounters(counters), equals(counters));
+      expect(echoU64s([u64Max, BigInt.one]), equals([u64Max, BigInt.one]));
+      expect(echoU64Map({'max': u64Max}), equals({'max': u64Max}));
+      expect(echoOptionalU64(u64Max), equals(u64Max));
+      expect(echoOptionalU64(null), isNull);
+    });
+
+    test('callback arguments and return values', () {
+      expect(transformWith(Halver(), u64Max), equals(u64Max >> 1));
+      expect(transformWith(Doubler(), u64Max >> 1), equals(u64Max - BigInt.one));
+    });
+
+    test('defaults', () {
+      final quota = Quota();
+      expect(quota.limit, equals(u64Max));
+      expect(quota.used, equals(BigInt.zero));
+      expect(quota.step, equals(BigInt.from(10)));
+      expect(Quota(used: BigInt.two).used, equals(BigInt.two));
+      expect(echoQuota(quota), equals(quota));
+
+      expect(advance(BigInt.one), equals(BigInt.two));
+      expect(advance(BigInt.one, step: BigInt.two), equals(BigInt.from(3)));
+      expect(advance(u64Max), equals(BigInt.zero));
+    });
+
+    test('custom types can opt out', () {
+      // An `int` holds the bits of the `u64`, so `u64::MAX` is -1
+      expect(echoNanos(42), equals(42));
+      expect(nanosIsMax(-1), isTrue);
+      expect(echoNanos(-1), equals(-1));
+    });
+  });
+}
+
+class Halver implements U64Transformer {
+  @override
+  BigInt transform(BigInt value) => value >> 1;
+}
+
+class Doubler implements U64Transformer {
+  @override
+  BigInt transform(BigInt value) => value * BigInt.two;
+}
@@ -0,0 +1,148 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+// Counts allocations that haven't been freed yet, so the tests can tell whether
+// every buffer crossing the FFI gets released by whichever side owns it.
+struct CountingAllocator;
+
+static LIVE_ALLOCATIONS: AtomicI64 = AtomicI64::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            LIVE_ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            LIVE_ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        LIVE_ALLOCATIONS.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[uniffi::export]
+pub fn live_allocations() -> i64 {
+    LIVE_ALLOCATIONS.load(Ordering::SeqCst)
+}
+
+#[derive(uniffi::Record)]
+pub struct Entry {
+    pub key: String,
+    pub tags: Vec<String>,
+    pub note: Option<String>,
+}
+
+#[derive(uniffi::Enum)]
+pub enum Level {
+    Low,
+    High,
+}
+
+#[derive(uniffi::Enum)]
+pub enum Shape {
+    Dot,
+    Label { text: String, level: Level },
+}
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum LookupError {
+    #[error("missing {key}")]
+    Missing { key: String },
+}
+
+#[uniffi::export]
+pub fn echo_string(value: String) -> String {
+    value
+}
+
+#[uniffi::export]
+pub fn echo_bytes(value: Vec<u8>) -> Vec<u8> {
+    value
+}
+
+#[uniffi::export]
+pub fn echo_entry(value: Entry) -> Entry {
+    value
+}
+
+#[uniffi::export]
+pub fn echo_entries(value: Vec<Entry>) -> Vec<Entry> {
+    value
+}
+
+#[uniffi::export]
+pub fn echo_map(value: HashMap<String, String>) -> HashMap<String, String> {
+    value
+}
+
+#[uniffi::export]
+pub fn echo_optional(value: Option<String>) -> Option<String> {
+    value
+}
+
+#[uniffi::export]
+pub fn echo_level(value: Level) -> Level {
+    value
+}
+
+#[uniffi::export]
+pub fn echo_shape(value: Shape) -> Shape {
+    value
+}
+
+#[uniffi::export]
+pub fn echo_duration(value: Duration) -> Duration {
+    value
+}
+
+#[uniffi::export]
+pub fn echo_timestamp(value: SystemTime) -> SystemTime {
+    value
+}
+
+#[uniffi::export]
+pub fn lookup(key: String) -> Result<String, LookupError> {
+    Err(LookupError::Missing { key })
+}
+
+#[derive(uniffi::Object)]
+pub struct Store {
+    entries: HashMap<String, String>,
+}
+
+#[uniffi::export]
+impl Store {
+    #[uniffi::constructor]
+    pub fn new(entries: HashMap<String, String>) -> Arc<Self> {
+        Arc::new(Self { entries })
+    }
+
+    pub fn get(&self, key: String) -> Result<String, LookupError> {
+        self.entries
+            .get(&key)
+            .cloned()
+            .ok_or(LookupError::Missing { key })
+    }
+}
+
+uniffi::include_scaffolding!("api");
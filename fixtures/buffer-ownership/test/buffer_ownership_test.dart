@@ -0,0 +1,77 @@
+import 'dart:typed_data';
+
+import 'package:test/test.dart';
+import '../buffer_ownership.dart';
+
+// Runs `body` once to let Rust settle any lazy state, then checks that many more
+// rounds leave the number of live Rust allocations where it was.
+void expectNoLeaks(void Function() body) {
+  body();
+  final before = liveAllocations();
+  for (var i = 0; i < 200; i++) {
+    body();
+  }
+  expect(liveAllocations(), equals(before));
+}
+
+void main() {
+  group('buffers crossing the FFI are freed', () {
+    test('strings and bytes', () {
+      expectNoLeaks(() {
+        expect(echoString('hello, buffers'), equals('hello, buffers'));
+        expect(echoString(''), equals(''));
+        expect(echoString('Ünïcödé — 日本語 🚀'), equals('Ünïcödé — 日本語 🚀'));
+        expect(echoBytes(Uint8List.fromList([1, 2, 3])), equals([1, 2, 3]));
+      });
+    });
+
+    test('records, sequences and maps', () {
+      final entry = Entry('key', ['a', 'b'], 'note');
+      expectNoLeaks(() {
+        expect(echoEntry(entry), equals(entry));
+        expect(echoEntries([entry, Entry('other', [], null)]).length, equals(2));
+        expect(echoMap({'a': '1', 'b': '2'}), equals({'a': '1', 'b': '2'}));
+      });
+    });
+
+    test('optionals', () {
+      expectNoLeaks(() {
+        expect(echoOptional('some'), equals('some'));
+        expect(echoOptional(null), isNull);
+      });
+    });
+
+    test('enums', () {
+      expectNoLeaks(() {
+        expect(echoLevel(Level.high), equals(Level.high));
+        final shape = echoShape(LabelShape(text: 'label', level: Level.low));
+        expect(shape, isA<LabelShape>());
+        expect((shape as LabelShape).text, equals('label'));
+        expect(echoShape(DotShape()), isA<DotShape>());
+      });
+    });
+
+    test('durations and timestamps', () {
+      final now = DateTime.utc(2024, 1, 2, 3, 4, 5);
+      expectNoLeaks(() {
+        expect(echoDuration(const Duration(seconds: 5)), equals(const Duration(seconds: 5)));
+        expect(echoTimestamp(now), equals(now));
+      });
+    });
+
+    test('error buffers', () {
+      expectNoLeaks(() {
+        expect(() => lookup('missing'), throwsA(isA<LookupException>()));
+      });
+    });
+
+    test('objects', () {
+      expectNoLeaks(() {
+        final store = Store({'a': '1'});
+        expect(store.get('a'), equals('1'));
+        expect(() => store.get('b'), throwsA(isA<LookupException>()));
+        store.dispose();
+      });
+    });
+  });
+}
@@ -0,0 +1,6 @@
+use anyhow::Result;
+
+#[test]
+fn buffer_ownership() -> Result<()> {
+    uniffi_dart::testing::run_test("buffer_ownership", "src/api.udl", None)
+}
@@ -1,104 +1,155 @@
-// use uniffi;
-// use url::Url;
-
-// pub struct Handle(pub i64);
-
-// pub struct TimeIntervalMs(pub i64);
-
-// pub struct TimeIntervalSecDbl(pub f64);
-
-// pub struct TimeIntervalSecFlt(pub f32);
-
-// impl UniffiCustomTypeConverter for Handle {
-//     // The `Builtin` type will be used to marshall values across the FFI
-//     type Builtin = i64;
-
-//     // Convert Builtin to our custom type
-//     fn into_custom(val: Self::Builtin) -> uniffi::Result<Self> {
-//         Ok(Handle(val))
-//     }
-
-//     // Convert our custom type to Builtin
-//     fn from_custom(obj: Self) -> Self::Builtin {
-//         obj.0
-//     }
-// }
-
-// impl UniffiCustomTypeConverter for Url {
-//     type Builtin = String;
-
-//     fn into_custom(val: Self::Builtin) -> uniffi::Result<Self> {
-//         Ok(Url::parse(&val)?)
-//     }
-
-//     fn from_custom(obj: Self) -> Self::Builtin {
-//         obj.into()
-//     }
-// }
-
-// impl UniffiCustomTypeConverter for TimeIntervalMs {
-//     // The `Builtin` type will be used to marshall values across the FFI
-//     type Builtin = i64;
-
-//     // Convert Builtin to our custom type
-//     fn into_custom(val: Self::Builtin) -> uniffi::Result<Self> {
-//         Ok(TimeIntervalMs(val))
-//     }
-
-//     // Convert our custom type to Builtin
-//     fn from_custom(obj: Self) -> Self::Builtin {
-//         obj.0
-//     }
-// }
-
-// impl UniffiCustomTypeConverter for TimeIntervalSecDbl {
-//     // The `Builtin` type will be used to marshall values across the FFI
-//     type Builtin = f64;
-
-//     // Convert Builtin to our custom type
-//     fn into_custom(val: Self::Builtin) -> uniffi::Result<Self> {
-//         Ok(TimeIntervalSecDbl(val))
-//     }
-
-//     // Convert our custom type to Builtin
-//     fn from_custom(obj: Self) -> Self::Builtin {
-//         obj.0
-//     }
-// }
-
-// impl UniffiCustomTypeConverter for TimeIntervalSecFlt {
-//     // The `Builtin` type will be used to marshall values across the FFI
-//     type Builtin = f32;
-
-//     // Convert Builtin to our custom type
-//     fn into_custom(val: Self::Builtin) -> uniffi::Result<Self> {
-//         Ok(TimeIntervalSecFlt(val))
-//     }
-
-//     // Convert our custom type to Builtin
-//     fn from_custom(obj: Self) -> Self::Builtin {
-//         obj.0
-//     }
-// }
-
-// #[derive(uniffi::Record)]
-// pub struct CustomTypesDemo {
-//     url: Url,
-//     handle: Handle,
-//     time_interval_ms: TimeIntervalMs,
-//     time_interval_sec_dbl: TimeIntervalSecDbl,
-//     time_interval_sec_flt: TimeIntervalSecFlt,
-// }
-
-// #[uniffi::export]
-// pub fn get_custom_types_demo(v: Option<CustomTypesDemo>) -> CustomTypesDemo {
-//     v.unwrap_or_else(|| CustomTypesDemo {
-//         url: Url::parse("http://example.com/").unwrap(),
-//         handle: Handle(123),
-//         time_interval_ms: TimeIntervalMs(456000),
-//         time_interval_sec_dbl: TimeIntervalSecDbl(456.0),
-//         time_interval_sec_flt: TimeIntervalSecFlt(777.0),
-//     })
-// }
-
-// uniffi::include_scaffolding!("api");
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use url::Url;
+
+pub struct Handle(pub i64);
+uniffi::custom_newtype!(Handle, i64);
+
+pub struct TimeIntervalMs(pub i64);
+uniffi::custom_newtype!(TimeIntervalMs, i64);
+
+pub struct TimeIntervalSecDbl(pub f64);
+uniffi::custom_newtype!(TimeIntervalSecDbl, f64);
+
+pub struct TimeIntervalSecFlt(pub f32);
+uniffi::custom_newtype!(TimeIntervalSecFlt, f32);
+
+uniffi::custom_type!(Url, String, {
+    remote,
+    try_lift: |val| Ok(Url::parse(&val)?),
+    lower: |obj| obj.into(),
+});
+
+// Custom types over builtins that aren't primitives
+
+#[derive(uniffi::Record)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+pub struct Position(pub Point);
+uniffi::custom_newtype!(Position, Point);
+
+#[derive(uniffi::Enum)]
+pub enum Direction {
+    North,
+    South,
+}
+
+pub struct Heading(pub Direction);
+uniffi::custom_newtype!(Heading, Direction);
+
+pub struct Tags(pub Vec<String>);
+uniffi::custom_newtype!(Tags, Vec<String>);
+
+pub struct Labels(pub HashMap<String, i32>);
+uniffi::custom_newtype!(Labels, HashMap<String, i32>);
+
+#[derive(uniffi::Object)]
+pub struct Counter {
+    value: u32,
+}
+
+#[uniffi::export]
+impl Counter {
+    #[uniffi::constructor]
+    pub fn new(value: u32) -> Self {
+        Self { value }
+    }
+
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+}
+
+pub struct CounterRef(pub Arc<Counter>);
+uniffi::custom_newtype!(CounterRef, Arc<Counter>);
+
+#[uniffi::export]
+pub fn move_north(position: Position) -> Position {
+    Position(Point {
+        x: position.0.x,
+        y: position.0.y + 1,
+    })
+}
+
+#[uniffi::export]
+pub fn reverse_heading(heading: Heading) -> Heading {
+    Heading(match heading.0 {
+        Direction::North => Direction::South,
+        Direction::South => Direction::North,
+    })
+}
+
+#[uniffi::export]
+pub fn sorted_tags(tags: Tags) -> Tags {
+    let mut tags = tags.0;
+    tags.sort();
+    Tags(tags)
+}
+
+#[uniffi::export]
+pub fn label_total(labels: Labels) -> i32 {
+    labels.0.values().sum()
+}
+
+#[uniffi::export]
+pub fn echo_labels(labels: Labels) -> Labels {
+    labels
+}
+
+#[uniffi::export]
+pub fn bump_counter(counter: CounterRef) -> CounterRef {
+    CounterRef(Arc::new(Counter::new(counter.0.value + 1)))
+}
+
+// The Rust scaffolding can't call a function shadowed by its argument, so only the exported name
+// is shared with the argument
+#[uniffi::export(name = "counters")]
+pub fn echo_counters(counters: Vec<CounterRef>) -> Vec<CounterRef> {
+    counters
+}
+
+#[derive(uniffi::Record)]
+pub struct CustomTypesDemo {
+    url: Url,
+    handle: Handle,
+    time_interval_ms: TimeIntervalMs,
+    time_interval_sec_dbl: TimeIntervalSecDbl,
+    time_interval_sec_flt: TimeIntervalSecFlt,
+}
+
+#[uniffi::export]
+pub fn get_custom_types_demo(v: Option<CustomTypesDemo>) -> CustomTypesDemo {
+    v.unwrap_or_else(|| CustomTypesDemo {
+        url: Url::parse("http://example.com/").unwrap(),
+        handle: Handle(123),
+        time_interval_ms: TimeIntervalMs(456000),
+        time_interval_sec_dbl: TimeIntervalSecDbl(456.0),
+        time_interval_sec_flt: TimeIntervalSecFlt(777.0),
+    })
+}
+
+#[uniffi::export]
+pub fn url_host(url: Url) -> String {
+    url.host_str().unwrap_or_default().to_string()
+}
+
+#[uniffi::export]
+pub fn get_optional_url(url: Option<Url>) -> Option<Url> {
+    url
+}
+
+#[uniffi::export]
+pub fn get_urls(urls: Vec<Url>) -> Vec<Url> {
+    urls
+}
+
+#[uniffi::export]
+pub fn get_url_map(urls: HashMap<String, Url>) -> HashMap<String, Url> {
+    urls
+}
+
+uniffi::include_scaffolding!("api");
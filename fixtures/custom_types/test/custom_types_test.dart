@@ -2,16 +2,65 @@ import 'package:test/test.dart';
 import '../custom_types.dart';
 
 void main() {
-  final api = Api.load();
-  
-  final demo = api.getCustomTypesDemo(null);
-
-  assert(demo.url.toString() == 'http://example.com/');
-  
-  assert(demo.handle == 123);
-
-  demo.url = Uri.parse('http://new.example.com/');
-  demo.handle = 456;
-  
-  assert(demo == api.getCustomTypesDemo(demo));
+  group('CustomTypes', () {
+    test('configured custom types use the Dart type', () {
+      final demo = getCustomTypesDemo(null);
+
+      expect(demo.url, isA<Uri>());
+      expect(demo.url, equals(Uri.parse('http://example.com/')));
+      expect(urlHost(Uri.parse('https://dart.dev/guides')), equals('dart.dev'));
+    });
+
+    test('extension types wrap the builtin type', () {
+      final demo = getCustomTypesDemo(null);
+
+      expect(demo.handle, equals(const Handle(123)));
+      expect(demo.handle.value, equals(123));
+      expect(demo.timeIntervalMs.value, equals(456000));
+    });
+
+    test('unconfigured custom types use the builtin type', () {
+      final demo = getCustomTypesDemo(null);
+
+      expect(demo.timeIntervalSecDbl, equals(456.0));
+      expect(demo.timeIntervalSecFlt, equals(777.0));
+    });
+
+    test('records round trip', () {
+      final demo = getCustomTypesDemo(null).copyWith(
+        url: Uri.parse('http://new.example.com/'),
+        handle: const Handle(456),
+      );
+
+      expect(getCustomTypesDemo(demo), equals(demo));
+    });
+
+    test('containers convert every element', () {
+      final urls = [Uri.parse('https://a.example/'), Uri.parse('https://b.example/')];
+
+      expect(getOptionalUrl(null), isNull);
+      expect(getOptionalUrl(urls.first), equals(urls.first));
+      expect(getUrls(urls), equals(urls));
+      expect(getUrlMap({'a': urls.first}), equals({'a': urls.first}));
+    });
+
+    test('custom types over records and enums', () {
+      expect(moveNorth(Point(1, 2)), equals(Point(1, 3)));
+      expect(reverseHeading(Direction.north), equals(Direction.south));
+    });
+
+    test('custom types over collections', () {
+      expect(sortedTags(['b', 'c', 'a']), equals(['a', 'b', 'c']));
+      expect(labelTotal({'a': 1, 'b': 2}), equals(3));
+      expect(echoLabels({'a': 1}), equals({'a': 1}));
+    });
+
+    test('custom types over objects', () {
+      expect(bumpCounter(Counter(41)).value(), equals(42));
+      expect(
+        counters([Counter(1), Counter(2)]).map((c) => c.value()),
+        equals([1, 2]),
+      );
+    });
+  });
 }
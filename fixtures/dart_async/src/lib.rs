@@ -1,9 +1,10 @@
-use uniffi;
-
 use std::{
     future::Future,
     pin::Pin,
-    sync::{Arc, Mutex, MutexGuard},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
     task::{Context, Poll, Waker},
     thread,
     time::Duration,
@@ -156,6 +157,15 @@ pub async fn sleep(ms: u16) -> bool {
 pub enum MyError {
     #[error("Foo")]
     Foo,
+    #[error("Bar: {reason}")]
+    Bar { reason: String },
+}
+
+// Failures of foreign implementations other than `MyError` reach Rust as this.
+impl From<uniffi::UnexpectedUniFFICallbackError> for MyError {
+    fn from(e: uniffi::UnexpectedUniFFICallbackError) -> Self {
+        MyError::Bar { reason: e.reason }
+    }
 }
 
 // An async function that can throw.
@@ -168,6 +178,62 @@ pub async fn fallible_me(do_fail: bool) -> Result<u8, MyError> {
     }
 }
 
+// Async functions whose results live in a `RustBuffer`, which must not be lifted on error.
+#[uniffi::export]
+pub async fn fallible_greeting(who: String, do_fail: bool) -> Result<String, MyError> {
+    TimerFuture::new(Duration::from_millis(10)).await;
+    if do_fail {
+        Err(MyError::Bar {
+            reason: format!("no greeting for {who}"),
+        })
+    } else {
+        Ok(format!("Hello, {who}!"))
+    }
+}
+
+#[uniffi::export]
+pub async fn fallible_record(do_fail: bool) -> Result<MyRecord, MyError> {
+    TimerFuture::new(Duration::from_millis(10)).await;
+    if do_fail {
+        Err(MyError::Foo)
+    } else {
+        Ok(MyRecord {
+            a: "foo".to_string(),
+            b: 42,
+        })
+    }
+}
+
+// Counts the futures of `wait_forever` dropped before they completed.
+static ABANDONED_WAITS: AtomicU32 = AtomicU32::new(0);
+
+struct AbandonGuard;
+
+impl Drop for AbandonGuard {
+    fn drop(&mut self) {
+        ABANDONED_WAITS.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+// An async function that only finishes when cancelled.
+#[uniffi::export]
+pub async fn wait_forever() -> String {
+    let _guard = AbandonGuard;
+    std::future::pending().await
+}
+
+#[uniffi::export]
+pub fn abandoned_waits() -> u32 {
+    ABANDONED_WAITS.load(Ordering::SeqCst)
+}
+
+// An async function whose future panics.
+#[uniffi::export]
+pub async fn panic_after(ms: u16, message: String) -> String {
+    TimerFuture::new(Duration::from_millis(ms.into())).await;
+    panic!("{message}");
+}
+
 #[uniffi::export(async_runtime = "tokio")]
 pub async fn say_after_with_tokio(ms: u16, who: String) -> String {
     tokio::time::sleep(Duration::from_millis(ms.into())).await;
@@ -200,10 +266,53 @@ pub async fn udl_always_ready() -> bool {
 }
 
 // UDL-defined async trait
+#[uniffi::trait_interface]
+#[async_trait::async_trait]
 pub trait SayAfterUdlTrait: Send + Sync {
     async fn say_after(&self, ms: u16, who: String) -> String;
 }
 
+// An async trait implemented in Dart, the way an HTTP client would be.
+#[uniffi::export(with_foreign)]
+#[async_trait::async_trait]
+pub trait Fetcher: Send + Sync {
+    async fn fetch(&self, url: String) -> String;
+    async fn fetch_status(&self, url: String) -> Result<u16, MyError>;
+    async fn is_reachable(&self, url: String) -> bool;
+    async fn close(&self);
+}
+
+// Fetches each url in turn, awaiting one Dart future after another.
+#[uniffi::export]
+pub async fn fetch_all(fetcher: Arc<dyn Fetcher>, urls: Vec<String>) -> Vec<String> {
+    let mut bodies = Vec::new();
+    for url in urls {
+        bodies.push(fetcher.fetch(url).await);
+    }
+    bodies
+}
+
+#[uniffi::export]
+pub async fn fetch_status_with(fetcher: Arc<dyn Fetcher>, url: String) -> Result<u16, MyError> {
+    fetcher.fetch_status(url).await
+}
+
+#[uniffi::export]
+pub async fn count_reachable(fetcher: Arc<dyn Fetcher>, urls: Vec<String>) -> u32 {
+    let mut reachable = 0;
+    for url in urls {
+        if fetcher.is_reachable(url).await {
+            reachable += 1;
+        }
+    }
+    reachable
+}
+
+#[uniffi::export]
+pub async fn close_fetcher(fetcher: Arc<dyn Fetcher>) {
+    fetcher.close().await
+}
+
 // UDL-defined object with async methods
 pub struct UdlMegaphone;
 
@@ -265,6 +374,26 @@ impl Megaphone {
             Ok(42)
         }
     }
+
+    /// Async method that can throw an error carrying data
+    pub async fn fallible_say(
+        self: Arc<Self>,
+        who: String,
+        do_fail: bool,
+    ) -> Result<String, MyError> {
+        TimerFuture::new(Duration::from_millis(10)).await;
+        if do_fail {
+            Err(MyError::Bar { reason: who })
+        } else {
+            Ok(format!("Hello, {who}!").to_uppercase())
+        }
+    }
+
+    /// Async method whose future panics
+    pub async fn panic_after(&self, ms: u16) {
+        TimerFuture::new(Duration::from_millis(ms.into())).await;
+        panic!("megaphone broke");
+    }
 }
 
 // Mixed async/sync methods on the same object (using tokio runtime)
@@ -329,6 +458,18 @@ impl FallibleMegaphone {
         TimerFuture::new(Duration::from_millis(10)).await;
         Err(MyError::Foo) // Always fails for testing
     }
+
+    #[uniffi::constructor]
+    pub async fn with_volume(volume: u8) -> Result<Arc<Self>, MyError> {
+        TimerFuture::new(Duration::from_millis(10)).await;
+        if volume > 10 {
+            Err(MyError::Bar {
+                reason: format!("volume {volume} is too loud"),
+            })
+        } else {
+            Ok(Arc::new(Self))
+        }
+    }
 }
 
 uniffi::include_scaffolding!("api");
@@ -1,3 +1,5 @@
+import 'dart:async';
+
 import 'package:test/test.dart';
 import '../dart_async.dart';
 
@@ -8,6 +10,43 @@ Future<Duration> measureTime(Future<void> Function() action) async {
   return end.difference(start);
 }
 
+// Serves a fixed set of pages, standing in for an HTTP client.
+class DartFetcher implements Fetcher {
+  final pages = {'a': 'page a', 'b': 'page b'};
+  bool closed = false;
+
+  @override
+  Future<String> fetch(String url) async {
+    await Future<void>.delayed(const Duration(milliseconds: 10));
+    final page = pages[url];
+    if (page == null) {
+      throw StateError('no page at $url');
+    }
+    return page;
+  }
+
+  @override
+  Future<int> fetchStatus(String url) async {
+    await Future<void>.delayed(const Duration(milliseconds: 10));
+    if (url == 'teapot') {
+      throw BarMyException('$url is a teapot');
+    }
+    if (url == 'broken') {
+      throw StateError('connection reset');
+    }
+    return pages.containsKey(url) ? 200 : 404;
+  }
+
+  @override
+  Future<bool> isReachable(String url) async => pages.containsKey(url);
+
+  @override
+  Future<void> close() async {
+    await Future<void>.delayed(const Duration(milliseconds: 10));
+    closed = true;
+  }
+}
+
 void main() {
   initialize();
   ensureInitialized();
@@ -77,26 +116,91 @@ void main() {
 
   test('fallible_function_and_method', () async {
     final time1 = await measureTime(() async {
-      try {
-        fallibleMe(false);
-        expect(true, true);
-      } catch (exception) {
-        expect(false, true); // should never be reached
-      }
+      expect(await fallibleMe(false), 42);
     });
     expect(time1.inMilliseconds <= 100, true);
 
     final time2 = await measureTime(() async {
-      try {
-        fallibleMe(true);
-        expect(false, true); // should never be reached
-      } catch (exception) {
-        expect(true, true);
-      }
+      await expectLater(fallibleMe(true), throwsA(isA<FooMyException>()));
     });
     expect(time2.inMilliseconds <= 100, true);
   });
 
+  test('fallible_functions_returning_buffers', () async {
+    expect(await fallibleGreeting('Alice', false), 'Hello, Alice!');
+    await expectLater(
+      fallibleGreeting('Bob', true),
+      throwsA(
+        isA<BarMyException>().having((e) => e.reason, 'reason', 'no greeting for Bob'),
+      ),
+    );
+
+    final record = await fallibleRecord(false);
+    expect(record.a, 'foo');
+    expect(record.b, 42);
+    await expectLater(fallibleRecord(true), throwsA(isA<MyException>()));
+  });
+
+  test('panicking_future', () async {
+    await expectLater(
+      panicAfter(10, 'future went wrong'),
+      throwsA(
+        isA<UniffiInternalError>()
+            .having((e) => e.errorCode, 'errorCode', UniffiInternalError.rustPanic)
+            .having((e) => e.panicMessage, 'panicMessage', contains('future went wrong')),
+      ),
+    );
+
+    // The runtime keeps working after a panic
+    expect(await sayAfter(10, 'Alice'), 'Hello, Alice!');
+  });
+
+  test('cancellation_token', () async {
+    final abandoned = abandonedWaits();
+    final token = UniffiCancellationToken();
+    final time = await measureTime(() async {
+      final pending = waitForever(cancellationToken: token);
+      Timer(const Duration(milliseconds: 100), token.cancel);
+      await expectLater(pending, throwsA(isA<UniffiCancelledException>()));
+    });
+    expect(time.inMilliseconds >= 100 && time.inMilliseconds < 300, true);
+    expect(abandonedWaits(), abandoned + 1);
+
+    // A cancelled token stops calls straight away
+    await expectLater(
+      sayAfter(100, 'Alice', cancellationToken: token),
+      throwsA(isA<UniffiCancelledException>()),
+    );
+
+    // Cancelling a token after the call finished is harmless
+    final other = UniffiCancellationToken();
+    expect(await sayAfter(10, 'Bob', cancellationToken: other), 'Hello, Bob!');
+    other.cancel();
+  });
+
+  test('timeout', () async {
+    final abandoned = abandonedWaits();
+    final time = await measureTime(() async {
+      await expectLater(
+        waitForever(timeout: const Duration(milliseconds: 100)),
+        throwsA(isA<TimeoutException>()),
+      );
+    });
+    expect(time.inMilliseconds >= 100 && time.inMilliseconds < 300, true);
+    expect(abandonedWaits(), abandoned + 1);
+
+    expect(
+      await sayAfter(10, 'Alice', timeout: const Duration(seconds: 1)),
+      'Hello, Alice!',
+    );
+
+    final megaphone = await Megaphone.create();
+    await expectLater(
+      megaphone.sayAfter(1000, 'Bob', timeout: const Duration(milliseconds: 50)),
+      throwsA(isA<TimeoutException>()),
+    );
+  });
+
   test('record', () async {
     final time = await measureTime(() async {
       final result = await newMyRecord('foo', 42);
@@ -129,7 +233,7 @@ void main() {
 
   test('proc_macro_megaphone_async_constructor', () async {
     final time = await measureTime(() async {
-      final megaphone = await Megaphone();
+      final megaphone = await Megaphone.create();
       expect(megaphone, isNotNull);
     });
     expect(time.inMilliseconds < 100, true);
@@ -144,7 +248,7 @@ void main() {
   });
 
   test('proc_macro_megaphone_async_methods', () async {
-    final megaphone = await Megaphone();
+    final megaphone = await Megaphone.create();
 
     // Test async method with timing
     final time = await measureTime(() async {
@@ -165,7 +269,7 @@ void main() {
   });
 
   test('proc_macro_megaphone_sync_method', () async {
-    final megaphone = await Megaphone();
+    final megaphone = await Megaphone.create();
 
     // Test sync method (should be immediate)
     final time = await measureTime(() async {
@@ -176,7 +280,7 @@ void main() {
   });
 
   test('proc_macro_megaphone_tokio_method', () async {
-    final megaphone = await Megaphone();
+    final megaphone = await Megaphone.create();
 
     final time = await measureTime(() async {
       final result = await megaphone.sayAfterWithTokio(100, 'Charlie');
@@ -186,25 +290,31 @@ void main() {
   });
 
   test('proc_macro_megaphone_fallible_method', () async {
-    final megaphone = await Megaphone();
+    final megaphone = await Megaphone.create();
 
     // Test success case
     final result = await megaphone.fallibleMe(false);
     expect(result, 42);
 
     // Test failure case
-    try {
-      await megaphone.fallibleMe(true);
-      expect(false, true); // Should never reach here
-    } catch (e) {
-      expect(true, true); // Expected to throw
-    }
+    await expectLater(megaphone.fallibleMe(true), throwsA(isA<FooMyException>()));
+
+    expect(await megaphone.fallibleSay('Alice', false), 'HELLO, ALICE!');
+    await expectLater(
+      megaphone.fallibleSay('Bob', true),
+      throwsA(isA<BarMyException>().having((e) => e.reason, 'reason', 'Bob')),
+    );
+
+    await expectLater(
+      megaphone.panicAfter(10),
+      throwsA(isA<UniffiInternalError>()),
+    );
   });
 
   test('udl_megaphone_async_constructors', () async {
     // Test primary constructor
     final time1 = await measureTime(() async {
-      final udlMegaphone = await UdlMegaphone();
+      final udlMegaphone = await UdlMegaphone.create();
       expect(udlMegaphone, isNotNull);
     });
     expect(time1.inMilliseconds < 100, true);
@@ -218,7 +328,7 @@ void main() {
   });
 
   test('udl_megaphone_async_method', () async {
-    final udlMegaphone = await UdlMegaphone();
+    final udlMegaphone = await UdlMegaphone.create();
 
     final time = await measureTime(() async {
       final result = await udlMegaphone.sayAfter(100, 'Dave');
@@ -245,7 +355,7 @@ void main() {
   });
 
   test('async_function_with_object_parameter', () async {
-    final megaphone = await Megaphone();
+    final megaphone = await Megaphone.create();
 
     final time = await measureTime(() async {
       final result = await sayAfterWithMegaphone(megaphone, 100, 'Eve');
@@ -260,21 +370,52 @@ void main() {
     expect(successResult, isNotNull);
 
     // Test failure case
-    try {
-      await fallibleStruct(true);
-      expect(false, true); // Should never reach here
-    } catch (e) {
-      expect(true, true); // Expected to throw
-    }
+    await expectLater(fallibleStruct(true), throwsA(isA<FooMyException>()));
   });
 
   test('fallible_async_constructor', () async {
     // This constructor always fails
-    try {
-      await FallibleMegaphone();
-      expect(false, true); // Should never reach here
-    } catch (e) {
-      expect(true, true); // Expected to throw
-    }
+    await expectLater(FallibleMegaphone.create(), throwsA(isA<FooMyException>()));
+
+    expect(await FallibleMegaphone.withVolume(5), isA<FallibleMegaphone>());
+    await expectLater(
+      FallibleMegaphone.withVolume(11),
+      throwsA(isA<BarMyException>().having((e) => e.reason, 'reason', 'volume 11 is too loud')),
+    );
+
+    // Async constructors can be cancelled like any other async call
+    await expectLater(
+      Megaphone.create(cancellationToken: UniffiCancellationToken()..cancel()),
+      throwsA(isA<UniffiCancelledException>()),
+    );
+  });
+
+  test('async_foreign_trait', () async {
+    final fetcher = DartFetcher();
+
+    expect(await fetchAll(fetcher, ['a', 'b']), equals(['page a', 'page b']));
+    expect(await countReachable(fetcher, ['a', 'missing', 'b']), equals(2));
+    expect(await fetchStatusWith(fetcher, 'a'), equals(200));
+    expect(await fetchStatusWith(fetcher, 'missing'), equals(404));
+
+    await closeFetcher(fetcher);
+    expect(fetcher.closed, isTrue);
+  });
+
+  test('async_foreign_trait_errors', () async {
+    final fetcher = DartFetcher();
+
+    // The method's declared error reaches Rust as is
+    await expectLater(
+      fetchStatusWith(fetcher, 'teapot'),
+      throwsA(isA<BarMyException>().having((e) => e.reason, 'reason', 'teapot is a teapot')),
+    );
+    // Any other exception is unexpected, and Rust converts it into the declared error
+    await expectLater(
+      fetchStatusWith(fetcher, 'broken'),
+      throwsA(isA<BarMyException>().having((e) => e.reason, 'reason', contains('connection reset'))),
+    );
+    // A method without a declared error panics in Rust instead
+    await expectLater(fetchAll(fetcher, ['a', 'missing']), throwsA(isA<UniffiInternalError>()));
   });
 }
@@ -90,6 +90,15 @@ pub fn test() -> Result<(), ErrorTest> {
 #[uniffi::export]
 pub fn test_multiline() {}
 
+/**
+    <docstring-block-function>
+
+    <docstring-block-paragraph>
+        <docstring-block-indented>
+*/
+#[uniffi::export]
+pub fn test_block_docstring() {}
+
 #[uniffi::export]
 pub fn test_without_docstring() -> Result<(), AssociatedErrorTest> {
     Ok(())
@@ -1,104 +1,140 @@
+import 'dart:io';
+
+import 'package:analyzer/dart/analysis/utilities.dart';
+import 'package:analyzer/dart/ast/ast.dart';
 import 'package:test/test.dart';
-import '../docstring_proc_macro.dart';
+// Prefixed, since the fixture's `test` function clashes with package:test
+import '../docstring_proc_macro.dart' as docstring;
 
 void main() {
-  group('Docstring Proc-Macro', () {
-    test('proc-macro enum with docstring', () {
-      final enumValue = EnumTest.one;
-      expect(enumValue, equals(EnumTest.one));
-
-      final enumValue2 = EnumTest.two;
-      expect(enumValue2, equals(EnumTest.two));
-    });
-
-    test('proc-macro associated enum with docstring', () {
-      // This test will fail until proc-macro support is implemented
-      // Expected: Associated enums should have documentation for variants and fields
+  // `dart test` runs from the package root, next to the generated bindings
+  final unit = parseString(
+    content: File('docstring_proc_macro.dart').readAsStringSync(),
+  ).unit;
+
+  AnnotatedNode declaration(String name) {
+    return unit.declarations.firstWhere(
+      (node) => node is NamedCompilationUnitMember && node.name.lexeme == name,
+    );
+  }
+
+  AnnotatedNode member(String container, String name) {
+    final members = switch (declaration(container)) {
+      ClassDeclaration(:final members) => members,
+      EnumDeclaration(:final constants) => constants,
+      final node => throw ArgumentError('$container is a ${node.runtimeType}'),
+    };
+    return members.firstWhere(
+      (node) => switch (node) {
+        MethodDeclaration(name: final id) ||
+        EnumConstantDeclaration(name: final id) =>
+          id.lexeme == name,
+        ConstructorDeclaration(name: final id) => (id?.lexeme ?? '') == name,
+        FieldDeclaration(:final fields) =>
+          fields.variables.any((field) => field.name.lexeme == name),
+        _ => false,
+      },
+    );
+  }
 
-      final associatedEnum = AssociatedEnumTest.test(code: 42);
-      expect(associatedEnum, isA<AssociatedEnumTest>());
+  void expectDocs(AnnotatedNode node, List<String> lines) {
+    expect(
+      node.documentationComment?.tokens.map((token) => token.lexeme),
+      equals(lines.map((line) => line.isEmpty ? '///' : '/// $line')),
+    );
+  }
 
-      final associatedEnum2 = AssociatedEnumTest.test2(code: 43);
-      expect(associatedEnum2, isA<AssociatedEnumTest>());
+  group('Docstring Proc-Macro', () {
+    test('functions', () {
+      expectDocs(declaration('test'), ['<docstring-function>']);
+      expectDocs(declaration('testMultiline'), [
+        '<docstring-multiline-function>',
+        '<second-line>',
+      ]);
+      expect(declaration('testWithoutDocstring').documentationComment, isNull);
+
+      docstring.test();
+      docstring.testMultiline();
+      docstring.testWithoutDocstring();
     });
 
-    test('proc-macro error with docstring', () {
-      // This test will fail until proc-macro support is implemented
-      // Expected: Error enums should have documentation on variants
-
-      expect(() => test(), throwsA(isA<ErrorTest>()));
+    test('block docstrings lose the source indentation', () {
+      expectDocs(declaration('testBlockDocstring'), [
+        '<docstring-block-function>',
+        '',
+        '<docstring-block-paragraph>',
+        '    <docstring-block-indented>',
+      ]);
     });
 
-    test('proc-macro associated error with docstring', () {
-      // This test will fail until proc-macro support is implemented
-      // Expected: Associated error enums should have documentation
-
-      expect(() => testWithoutDocstring(), throwsA(isA<AssociatedErrorTest>()));
+    test('long docstrings', () {
+      final line =
+          'This is a very long multi line test docstring that exceeds 255 characters.';
+      expectDocs(declaration('testLongDocstring'), [line, line, line, line]);
     });
 
-    test('proc-macro object with docstring', () {
-      // This test will fail until proc-macro support is implemented
-      // Expected: Objects should have documentation on constructors and methods
-
-      final obj = ObjectTest();
-      obj.test();
-
-      final objAlt = ObjectTest.newAlternate();
-      objAlt.test();
+    test('enums and variants', () {
+      expectDocs(declaration('EnumTest'), ['<docstring-enum>']);
+      expectDocs(member('EnumTest', 'one'), ['<docstring-enum-variant>']);
+      expectDocs(member('EnumTest', 'two'), ['<docstring-enum-variant-2>']);
+
+      expectDocs(declaration('AssociatedEnumTest'), [
+        '<docstring-associated-enum>',
+      ]);
+      expectDocs(declaration('TestAssociatedEnumTest'), [
+        '<docstring-associated-enum-variant>',
+      ]);
+      expectDocs(member('TestAssociatedEnumTest', 'code'), [
+        '<docstring-variant-field>',
+      ]);
+      expectDocs(declaration('Test2AssociatedEnumTest'), [
+        '<docstring-associated-enum-variant-2>',
+      ]);
+
+      expect(docstring.TestAssociatedEnumTest(42).code, equals(42));
     });
 
-    test('proc-macro record with docstring', () {
-      // This test will fail until proc-macro support is implemented
-      // Expected: Records should have documentation on fields
-
-      final record = RecordTest(test: 42);
-      expect(record.test, equals(42));
+    test('errors and variants', () {
+      expectDocs(declaration('ErrorTest'), ['<docstring-error>']);
+      expectDocs(declaration('OneErrorTest'), ['<docstring-error-variant>']);
+      expectDocs(declaration('TwoErrorTest'), ['<docstring-error-variant-2>']);
+
+      expectDocs(declaration('AssociatedErrorTest'), [
+        '<docstring-associated-error>',
+      ]);
+      expectDocs(declaration('TestAssociatedErrorTest'), [
+        '<docstring-associated-error-variant>',
+      ]);
+      expectDocs(declaration('Test2AssociatedErrorTest'), [
+        '<docstring-associated-error-variant-2>',
+      ]);
     });
 
-    test('proc-macro function with docstring', () {
-      // This test will fail until proc-macro support is implemented
-      // Expected: Functions should have proper documentation comments in generated Dart
-
-      test();
-      testMultiline();
-      testWithoutDocstring();
+    test('objects, constructors and methods', () {
+      expectDocs(declaration('ObjectTest'), ['<docstring-object>']);
+      expectDocs(member('ObjectTest', ''), ['<docstring-primary-constructor>']);
+      expectDocs(member('ObjectTest', 'newAlternate'), [
+        '<docstring-alternate-constructor>',
+      ]);
+      expectDocs(member('ObjectTest', 'test'), ['<docstring-method>']);
+      expectDocs(member('ObjectTestInterface', 'test'), ['<docstring-method>']);
+
+      docstring.ObjectTest().test();
+      docstring.ObjectTest.newAlternate().test();
     });
 
-    test(
-      'proc-macro callback interface with docstring',
-      () {
-        // This test will fail until callback interface support is implemented
-        // Expected: Callback interfaces should have documentation on methods
-
-        // final callback = CallbackTestImpl();
-        // callback.test();
-      },
-      skip: 'Requires callback interface implementation',
-    );
-
-    test('proc-macro long docstring', () {
-      // This test will fail until proc-macro support is implemented
-      // Expected: Long docstrings (>255 chars) should be properly handled
+    test('records and fields', () {
+      expectDocs(declaration('RecordTest'), ['<docstring-record>']);
+      expectDocs(member('RecordTest', 'test'), ['<docstring-record-field>']);
 
-      testLongDocstring();
+      expect(docstring.RecordTest(42).test, equals(42));
     });
 
-    test('documentation generation comparison', () {
-      // This test compares proc-macro docs vs UDL docs
-      // Expected: Both should generate equivalent documentation in Dart
-
-      // Test basic functionality to ensure proc-macro definitions work
-      final enumValue = EnumTest.one;
-      expect(enumValue, equals(EnumTest.one));
-
-      test();
-      testMultiline();
-
-      final obj = ObjectTest();
-      obj.test();
-
-      final record = RecordTest(test: 123);
-      expect(record.test, equals(123));
+    test('callback interfaces and methods', () {
+      expectDocs(declaration('CallbackTest'), ['<docstring-callback>']);
+      expectDocs(member('CallbackTest', 'test'), [
+        '<docstring-callback-method>',
+      ]);
     });
   });
 }
@@ -1,7 +1,6 @@
-#[cfg(test)]
-mod tests {
-    #[test]
-    fn test_docstring_proc_macro() {
-        uniffi_dart::testing::run_test("docstring_proc_macro", None).unwrap();
-    }
+use anyhow::Result;
+
+#[test]
+fn docstring_proc_macro() -> Result<()> {
+    uniffi_dart::testing::run_test("docstring_proc_macro", "src/api.udl", None)
 }
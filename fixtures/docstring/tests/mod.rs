@@ -1,7 +1,6 @@
-#[cfg(test)]
-mod tests {
-    #[test]
-    fn test_docstring() {
-        uniffi_dart::testing::run_test("docstring", "src/api.udl", None).unwrap();
-    }
+use anyhow::Result;
+
+#[test]
+fn docstring() -> Result<()> {
+    uniffi_dart::testing::run_test("docstring", "src/api.udl", None)
 }
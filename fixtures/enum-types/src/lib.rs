@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+#[derive(uniffi::Enum)]
 pub enum Animal {
     Dog,
     Cat,
@@ -94,9 +95,97 @@ pub enum AnimalNamedEnum {
     Cat { record: AnimalRecord },
 }
 
+#[non_exhaustive]
+#[derive(uniffi::Enum)]
+pub enum Planet {
+    Mercury,
+    Venus,
+}
+
+#[non_exhaustive]
+#[derive(uniffi::Enum)]
+pub enum Shape {
+    Circle { radius: f64 },
+    Square { side: f64 },
+}
+
+#[uniffi::export]
+pub fn identity_planet(p: Planet) -> Planet {
+    p
+}
+
+#[uniffi::export]
+pub fn identity_shape(s: Shape) -> Shape {
+    s
+}
+
+// Enums with data inside other values, where they don't start at the beginning of the buffer
+#[derive(uniffi::Record)]
+pub struct Drawing {
+    name: String,
+    shape: Shape,
+    shapes: Vec<Shape>,
+    highlight: Option<Shape>,
+    layer: u32,
+}
+
+#[uniffi::export]
+pub fn identity_drawing(d: Drawing) -> Drawing {
+    d
+}
+
 #[uniffi::export]
 pub fn get_animal(a: Option<Animal>) -> Animal {
     a.unwrap_or(Animal::Dog)
 }
 
+#[uniffi::export]
+pub fn identity_animal_uint(a: AnimalUInt) -> AnimalUInt {
+    a
+}
+
+#[uniffi::export]
+pub fn identity_animal_large_uint(a: AnimalLargeUInt) -> AnimalLargeUInt {
+    a
+}
+
+#[uniffi::export]
+pub fn identity_animal_signed_int(a: AnimalSignedInt) -> AnimalSignedInt {
+    a
+}
+
+#[uniffi::export]
+pub fn animal_signed_int_value(a: AnimalSignedInt) -> i8 {
+    a as i8
+}
+
+// Flat errors carry their `Display` message across, but not their fields
+#[derive(Debug, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum ParseAnimalError {
+    Unknown(String),
+    Empty,
+}
+
+impl std::fmt::Display for ParseAnimalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unknown(name) => write!(f, "unknown animal: {name}"),
+            Self::Empty => write!(f, "no animal given"),
+        }
+    }
+}
+
+impl std::error::Error for ParseAnimalError {}
+
+#[uniffi::export]
+pub fn parse_animal(name: String) -> Result<Animal, ParseAnimalError> {
+    match name.as_str() {
+        "dog" => Ok(Animal::Dog),
+        "cat" => Ok(Animal::Cat),
+        "" => Err(ParseAnimalError::Empty),
+        _ => Err(ParseAnimalError::Unknown(name)),
+    }
+}
+
 uniffi::include_scaffolding!("api");
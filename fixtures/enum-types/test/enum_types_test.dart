@@ -1,3 +1,5 @@
+import 'dart:typed_data';
+
 import 'package:test/test.dart';
 import '../enum_types.dart';
 
@@ -13,6 +15,98 @@ void main() {
       expect(getAnimal(Animal.cat), equals(Animal.cat));
     });
 
+    test('explicit discriminants', () {
+      expect(AnimalUInt.dog.value, equals(3));
+      expect(AnimalUInt.cat.value, equals(4));
+      expect(AnimalLargeUInt.dog.value, equals(4294967298));
+      expect(AnimalSignedInt.dog.value, equals(-3));
+      expect(AnimalSignedInt.koala.value, equals(-1));
+      expect(AnimalSignedInt.wombat.value, equals(1));
+
+      // Implicit discriminants count up from zero
+      expect(Animal.dog.value, equals(0));
+      expect(Animal.cat.value, equals(1));
+
+      expect(AnimalUInt.fromValue(4), equals(AnimalUInt.cat));
+      expect(AnimalSignedInt.fromValue(0), equals(AnimalSignedInt.wallaby));
+      expect(() => AnimalUInt.fromValue(0), throwsArgumentError);
+    });
+
+    test('discriminants do not change the wire format', () {
+      expect(identityAnimalUint(AnimalUInt.cat), equals(AnimalUInt.cat));
+      expect(
+        identityAnimalLargeUint(AnimalLargeUInt.dog),
+        equals(AnimalLargeUInt.dog),
+      );
+      for (final animal in AnimalSignedInt.values) {
+        expect(identityAnimalSignedInt(animal), equals(animal));
+        expect(animalSignedIntValue(animal), equals(animal.value));
+      }
+    });
+
+    test('non-exhaustive enums lift unknown variants', () {
+      final unknownIndex = Uint8List(4)..buffer.asByteData().setInt32(0, 42);
+
+      expect(identityPlanet(Planet.venus), equals(Planet.venus));
+      expect(Planet.venus.index, equals(1));
+      expect(Planet.values, equals([Planet.mercury, Planet.venus]));
+      final planet = FfiConverterPlanet.read(unknownIndex).value;
+      expect(planet, equals(Planet.unknown(41)));
+      expect(planet.isUnknown, isTrue);
+      expect(planet.index, equals(41));
+      expect(planet.value, isNull);
+      expect(() => FfiConverterPlanet.lower(planet), throwsUnsupportedError);
+
+      expect(identityShape(CircleShape(1.5)), equals(CircleShape(1.5)));
+      final shape = FfiConverterShape.lift(toRustBuffer(unknownIndex));
+      expect(shape, equals(UnknownShape(41)));
+      expect((shape as UnknownShape).index, equals(41));
+      expect(() => FfiConverterShape.lower(shape), throwsUnsupportedError);
+    });
+
+    test('enums with data round-trip inside other values', () {
+      final drawing = Drawing(
+        'shapes',
+        SquareShape(2.0),
+        [CircleShape(1.0), SquareShape(3.0)],
+        CircleShape(0.5),
+        7,
+      );
+      expect(identityDrawing(drawing), equals(drawing));
+
+      final size = FfiConverterShape.allocationSize(drawing.shape);
+      final buf = Uint8List.sublistView(Uint8List(size + 8), 8);
+      expect(FfiConverterShape.write(drawing.shape, buf), equals(size));
+      final lifted = FfiConverterShape.read(buf);
+      expect(lifted.value, equals(drawing.shape));
+      expect(lifted.bytesRead, equals(size));
+    });
+
+    test('unknown variants with data fail inside other values', () {
+      // The payload of an unknown variant can't be skipped, so any following fields would be misread
+      final unknownIndex = Uint8List(4)..buffer.asByteData().setInt32(0, 42);
+      expect(
+        () => FfiConverterShape.read(unknownIndex),
+        throwsA(isA<UniffiInternalError>()),
+      );
+    });
+
+    test('flat errors keep their message', () {
+      expect(parseAnimal('cat'), equals(Animal.cat));
+      expect(
+        () => parseAnimal('cow'),
+        throwsA(isA<UnknownParseAnimalException>()
+            .having((e) => e.message, 'message', 'unknown animal: cow')
+            .having((e) => e.toString(), 'toString', 'UnknownParseAnimalException(unknown animal: cow)')),
+      );
+      expect(
+        () => parseAnimal(''),
+        throwsA(isA<EmptyParseAnimalException>().having((e) => e.message, 'message', 'no animal given')),
+      );
+      expect(UnknownParseAnimalException('a'), equals(UnknownParseAnimalException('a')));
+      expect(EmptyParseAnimalException().message, isEmpty);
+    });
+
     test('enum containers', () {
       // Test complex enums with objects and records
       final dogEnum = getAnimalEnum(Animal.dog);
@@ -26,6 +120,18 @@ void main() {
       expect(dogEnum.toString(), isNot(equals(catEnum.toString())));
     });
 
+    test('enums with data are sealed', () {
+      // Without a default case this switch only compiles against a sealed hierarchy
+      String describe(AnimalEnum animal) => switch (animal) {
+            NoneAnimalEnum() => 'none',
+            DogAnimalEnum() => 'dog',
+            CatAnimalEnum() => 'cat',
+          };
+
+      expect(describe(getAnimalEnum(Animal.dog)), equals('dog'));
+      expect(describe(getAnimalEnum(Animal.cat)), equals('cat'));
+    });
+
     test('enum equality', () {
       // Test enum equality
       final dogEnum1 = getAnimalEnum(Animal.dog);
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+// A patch where a missing field leaves the value alone and `Some(None)` clears it
+#[derive(uniffi::Record)]
+pub struct Patch {
+    pub name: Option<Option<String>>,
+    pub age: Option<Option<u32>>,
+    pub tags: Option<Option<Vec<String>>>,
+}
+
+#[derive(uniffi::Record)]
+pub struct Person {
+    pub name: Option<String>,
+    pub age: Option<u32>,
+    pub tags: Option<Vec<String>>,
+}
+
+fn apply<T>(field: &mut Option<T>, update: Option<Option<T>>) {
+    if let Some(value) = update {
+        *field = value;
+    }
+}
+
+#[uniffi::export]
+pub fn apply_patch(mut person: Person, patch: Patch) -> Person {
+    apply(&mut person.name, patch.name);
+    apply(&mut person.age, patch.age);
+    apply(&mut person.tags, patch.tags);
+    person
+}
+
+#[uniffi::export]
+pub fn echo_nested(value: Option<Option<i32>>) -> Option<Option<i32>> {
+    value
+}
+
+#[uniffi::export]
+pub fn describe_nested(value: Option<Option<String>>) -> String {
+    match value {
+        None => "None".to_string(),
+        Some(None) => "Some(None)".to_string(),
+        Some(Some(s)) => format!("Some(Some({s}))"),
+    }
+}
+
+#[uniffi::export]
+pub fn some_none() -> Option<Option<String>> {
+    Some(None)
+}
+
+#[uniffi::export]
+pub fn echo_triple(value: Option<Option<Option<bool>>>) -> Option<Option<Option<bool>>> {
+    value
+}
+
+#[uniffi::export]
+pub fn echo_nested_list(values: Vec<Option<Option<i32>>>) -> Vec<Option<Option<i32>>> {
+    values
+}
+
+#[uniffi::export]
+pub fn echo_nested_map(
+    values: HashMap<String, Option<Option<i32>>>,
+) -> HashMap<String, Option<Option<i32>>> {
+    values
+}
+
+// Single optionals are unaffected
+#[uniffi::export]
+pub fn echo_single(value: Option<i32>) -> Option<i32> {
+    value
+}
+
+uniffi::include_scaffolding!("api");
@@ -0,0 +1,53 @@
+import 'package:test/test.dart';
+import '../nested_optionals.dart';
+
+void main() {
+  group('nested optionals', () {
+    test('Some(None) and None round-trip separately', () {
+      expect(echoNested(const None()), equals(const None<int?>()));
+      expect(echoNested(const Some(null)), equals(const Some<int?>(null)));
+      expect(echoNested(const Some(7)), equals(const Some<int?>(7)));
+    });
+
+    test('Rust sees which one was passed', () {
+      expect(describeNested(const None()), equals('None'));
+      expect(describeNested(const Some(null)), equals('Some(None)'));
+      expect(describeNested(const Some('x')), equals('Some(Some(x))'));
+      expect(someNone(), isA<Some<String?>>());
+      expect((someNone() as Some<String?>).value, isNull);
+    });
+
+    test('record fields patch only what is present', () {
+      final person = Person('Ann', 40, ['a']);
+      final unchanged = applyPatch(person, Patch(const None(), const None(), const None()));
+      expect(unchanged, equals(person));
+
+      final cleared = applyPatch(person, Patch(const Some(null), const None(), const Some(null)));
+      expect(cleared, equals(Person(null, 40, null)));
+
+      final updated = applyPatch(person, Patch(const Some('Bob'), const Some(41), const Some(['b', 'c'])));
+      expect(updated, equals(Person('Bob', 41, ['b', 'c'])));
+      expect(Patch(const Some(null), const None(), const Some(['b'])),
+          equals(Patch(const Some(null), const None(), const Some(['b']))));
+    });
+
+    test('deeper nesting', () {
+      expect(echoTriple(const None()), equals(const None<Optional<bool?>>()));
+      expect(echoTriple(const Some(None())), equals(const Some<Optional<bool?>>(None())));
+      expect(echoTriple(const Some(Some(null))), equals(const Some<Optional<bool?>>(Some(null))));
+      expect(echoTriple(const Some(Some(true))), equals(const Some<Optional<bool?>>(Some(true))));
+    });
+
+    test('inside sequences and maps', () {
+      final values = <Optional<int?>>[const None(), const Some(null), const Some(3)];
+      expect(echoNestedList(values), equals(values));
+      final map = <String, Optional<int?>>{'none': const None(), 'null': const Some(null)};
+      expect(echoNestedMap(map), equals(map));
+    });
+
+    test('single optionals stay nullable', () {
+      expect(echoSingle(null), isNull);
+      expect(echoSingle(5), equals(5));
+    });
+  });
+}
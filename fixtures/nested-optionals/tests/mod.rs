@@ -0,0 +1,17 @@
+use anyhow::Result;
+
+#[test]
+fn nested_optionals() -> Result<()> {
+    uniffi_dart::testing::run_test("nested_optionals", "src/api.udl", Some("uniffi.toml"))
+}
+
+// Library mode has to find `uniffi.toml` on its own for the nested optional wrappers.
+#[test]
+fn nested_optionals_library_mode() -> Result<()> {
+    uniffi_dart::testing::run_test_with_config(
+        "nested_optionals",
+        "src/api.udl",
+        None,
+        &uniffi_dart::testing::TestConfig::from_env().with_library_mode(true),
+    )
+}
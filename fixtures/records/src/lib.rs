@@ -0,0 +1,27 @@
+use std::collections::HashMap;
+
+#[derive(uniffi::Record)]
+pub struct Contact {
+    pub name: String,
+    pub email: Option<String>,
+    pub tags: Vec<String>,
+    pub scores: HashMap<String, u32>,
+}
+
+#[derive(uniffi::Enum)]
+pub enum Shape {
+    Circle { radius: f64 },
+    Rectangle { width: f64, height: f64 },
+}
+
+#[uniffi::export]
+pub fn echo_contact(contact: Contact) -> Contact {
+    contact
+}
+
+#[uniffi::export]
+pub fn echo_shape(shape: Shape) -> Shape {
+    shape
+}
+
+uniffi::include_scaffolding!("api");
@@ -0,0 +1,83 @@
+import 'package:test/test.dart';
+import '../records.dart';
+
+void main() {
+  Contact contact() => Contact('Ada', 'ada@example.com', ['math'], {'chess': 3});
+
+  group('Records', () {
+    test('have value semantics', () {
+      expect(contact(), equals(contact()));
+      expect(contact().hashCode, equals(contact().hashCode));
+      expect(echoContact(contact()), equals(contact()));
+      expect({contact(): 'value'}[contact()], equals('value'));
+      expect(contact(), isNot(equals(contact().copyWith(tags: []))));
+      expect(
+        contact(),
+        isNot(equals(contact().copyWith(scores: {'chess': 4}))),
+      );
+      expect(
+        contact().toString(),
+        equals(
+          'Contact(name: Ada, email: ada@example.com, '
+          'tags: [math], scores: {chess: 3})',
+        ),
+      );
+    });
+
+    test('copyWith keeps omitted fields', () {
+      final copy = contact().copyWith(name: 'Grace');
+      expect(copy.name, equals('Grace'));
+      expect(copy.email, equals('ada@example.com'));
+      expect(copy.tags, equals(['math']));
+      expect(copy.scores, equals({'chess': 3}));
+    });
+
+    test('copyWith can clear a nullable field', () {
+      final cleared = contact().copyWith(email: null);
+      expect(cleared.email, isNull);
+      expect(cleared.name, equals('Ada'));
+      expect(cleared.copyWith(email: 'a@b.c').email, equals('a@b.c'));
+    });
+  });
+
+  group('Enum variants', () {
+    test('have value semantics', () {
+      expect(ShapeCircle(1.0), equals(ShapeCircle(1.0)));
+      expect(ShapeCircle(1.0).hashCode, equals(ShapeCircle(1.0).hashCode));
+      expect(echoShape(ShapeCircle(1.0)), equals(ShapeCircle(1.0)));
+      expect(
+        ShapeCircle(1.0),
+        isNot(equals(ShapeRectangle(width: 1.0, height: 1.0))),
+      );
+
+      final rectangle = ShapeRectangle(width: 1.0, height: 2.0);
+      expect(
+        rectangle.copyWith(height: 3.0),
+        equals(ShapeRectangle(width: 1.0, height: 3.0)),
+      );
+      expect(
+        rectangle.toString(),
+        equals('ShapeRectangle(width: 1.0, height: 2.0)'),
+      );
+    });
+
+    test('are named after the configured pattern', () {
+      final Shape shape = ShapeCircle(1.0);
+      expect(shape.runtimeType.toString(), equals('ShapeCircle'));
+    });
+
+    test('support exhaustive pattern matching', () {
+      String describe(Shape shape) => switch (shape) {
+            ShapeCircle(:final radius) => 'circle of radius $radius',
+            ShapeRectangle(:final width, :final height) =>
+              'rectangle of $width by $height',
+          };
+
+      expect(describe(ShapeCircle(1.0)), equals('circle of radius 1.0'));
+      expect(
+        describe(echoShape(ShapeRectangle(width: 2.0, height: 3.0))),
+        equals('rectangle of 2.0 by 3.0'),
+      );
+    });
+  });
+}
@@ -0,0 +1,6 @@
+use anyhow::Result;
+
+#[test]
+fn records() -> Result<()> {
+    uniffi_dart::testing::run_test("records", "src/api.udl", Some("uniffi.toml"))
+}
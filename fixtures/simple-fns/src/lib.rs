@@ -43,6 +43,14 @@ fn set_contains(set: Arc<MyHashSet>, value: String) -> bool {
 // other generic builtin types) wasn't being added as a known type.
 fn dummy(_arg: Option<i8>) {}
 
+fn greet(name: String, greeting: String, times: u32) -> String {
+    vec![format!("{greeting}, {name}!"); times as usize].join(" ")
+}
+
+fn report_status(status: String) -> String {
+    format!("status: {status}")
+}
+
 // MyHashSet implementation
 pub struct MyHashSet {
     inner: Mutex<HashSet<String>>,
@@ -55,6 +63,24 @@ impl MyHashSet {
         }
     }
 
+    pub fn with_values(values: Vec<String>) -> Self {
+        Self {
+            inner: Mutex::new(values.into_iter().collect()),
+        }
+    }
+
+    pub fn count_matching(&self, prefix: String, case_sensitive: bool) -> u32 {
+        let inner = self.inner.lock().unwrap();
+        let matches = |value: &&String| {
+            if case_sensitive {
+                value.starts_with(&prefix)
+            } else {
+                value.to_lowercase().starts_with(&prefix.to_lowercase())
+            }
+        };
+        inner.iter().filter(matches).count() as u32
+    }
+
     pub fn add(&self, value: String) {
         self.inner.lock().unwrap().insert(value);
     }
@@ -24,6 +24,25 @@ void main() {
       expect(byteToU32(128), 128);
     });
 
+    test('greet fills in default arguments', () {
+      expect(greet('Dart'), 'Hello, Dart!');
+      expect(greet('Dart', greeting: 'Hi'), 'Hi, Dart!');
+      expect(greet('Dart', times: 2), 'Hello, Dart! Hello, Dart!');
+    });
+
+    test('arguments named like locals of the call', () {
+      expect(reportStatus('ok'), 'status: ok');
+    });
+
+    test('constructor and method default arguments', () {
+      final empty = MyHashSet.withValues();
+      expect(empty.countMatching('a'), 0);
+
+      final set = MyHashSet.withValues(values: ['apple', 'Avocado', 'banana']);
+      expect(set.countMatching('a'), 1);
+      expect(set.countMatching('a', caseSensitive: false), 2);
+    });
+
     test('hash_map_identity returns same map', () {
       final testMap = {'a': 'b', 'hello': 'world'};
       expect(hashMapIdentity(testMap), testMap);
@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+use std::time::SystemTime;
+
 pub struct Bookmark {
     pub guid: Option<String>,
     pub position: i32,
@@ -6,4 +9,68 @@ pub struct Bookmark {
     pub title: Option<String>,
 }
 
+pub enum Priority {
+    Low,
+    High,
+}
+
+pub struct Settings {
+    pub name: String,
+    pub retries: u32,
+    pub enabled: bool,
+    pub ratio: f64,
+    pub priority: Priority,
+    pub tags: Vec<String>,
+    pub note: Option<String>,
+}
+
+pub fn echo_settings(settings: Settings) -> Settings {
+    settings
+}
+
+// Proc-macro records declare their defaults with `#[uniffi(default)]`, which covers types without
+// constant Dart values as well
+#[derive(uniffi::Record)]
+pub struct Margins {
+    #[uniffi(default = 8)]
+    pub top: u32,
+    #[uniffi(default = 8)]
+    pub bottom: u32,
+}
+
+#[derive(uniffi::Record)]
+pub struct Window {
+    pub title: String,
+    #[uniffi(default)]
+    pub width: u32,
+    #[uniffi(default = 480)]
+    pub height: u32,
+    #[uniffi(default)]
+    pub icon: Vec<u8>,
+    #[uniffi(default)]
+    pub opened_at: SystemTime,
+    #[uniffi(default)]
+    pub margins: Margins,
+    #[uniffi(default)]
+    pub labels: HashMap<String, String>,
+    #[uniffi(default = None)]
+    pub note: Option<String>,
+}
+
+#[uniffi::export]
+pub fn echo_window(window: Window) -> Window {
+    window
+}
+
+#[uniffi::export(default(scale = 2, icon = []))]
+pub fn describe_window(window: Window, scale: u32, icon: Vec<u8>) -> String {
+    format!(
+        "{} {}x{} {} bytes",
+        window.title,
+        window.width * scale,
+        window.height * scale,
+        icon.len()
+    )
+}
+
 uniffi::include_scaffolding!("api");
@@ -1,3 +1,5 @@
+import 'dart:typed_data';
+
 import 'package:test/test.dart';
 import '../struct_default_values.dart';
 
@@ -46,5 +48,68 @@ void main() {
       expect(bookmark.lastModified, equals(1234567890));
       expect(bookmark.title, equals("Full Bookmark"));
     });
+
+    test('settings use rendered defaults', () {
+      final settings = Settings();
+
+      expect(settings.name, equals("default"));
+      expect(settings.retries, equals(3));
+      expect(settings.enabled, isTrue);
+      expect(settings.ratio, equals(0.5));
+      expect(settings.priority, equals(Priority.high));
+      expect(settings.tags, isEmpty);
+      expect(settings.note, isNull);
+    });
+
+    test('settings round trip', () {
+      final settings = Settings(retries: 5, tags: ["a", "b"], note: "hi");
+
+      expect(echoSettings(settings), equals(settings));
+      expect(settings.copyWith(enabled: false).enabled, isFalse);
+      expect(settings.copyWith(enabled: false).retries, equals(5));
+    });
+  });
+
+  group('ProcMacroDefaultValues', () {
+    test('window uses constant and built defaults', () {
+      final window = Window(title: "main");
+
+      expect(window.width, equals(0));
+      expect(window.height, equals(480));
+      expect(window.icon, isEmpty);
+      expect(
+        window.openedAt,
+        equals(DateTime.fromMillisecondsSinceEpoch(0, isUtc: true)),
+      );
+      expect(window.margins, equals(Margins(top: 8, bottom: 8)));
+      expect(window.labels, isEmpty);
+      expect(window.note, isNull);
+      expect(echoWindow(window), equals(window));
+    });
+
+    test('window keeps explicit values', () {
+      final openedAt = DateTime.utc(2024, 1, 1);
+      final window = Window(
+        title: "main",
+        icon: Uint8List.fromList([1, 2]),
+        openedAt: openedAt,
+        margins: Margins(top: 0),
+      );
+
+      expect(window.icon, equals([1, 2]));
+      expect(window.openedAt, equals(openedAt));
+      expect(window.margins, equals(Margins(top: 0, bottom: 8)));
+      expect(echoWindow(window), equals(window));
+    });
+
+    test('arguments use constant and built defaults', () {
+      final window = Window(title: "main", width: 10, height: 20);
+
+      expect(describeWindow(window), equals("main 20x40 0 bytes"));
+      expect(
+        describeWindow(window, scale: 1, icon: Uint8List(3)),
+        equals("main 10x20 3 bytes"),
+      );
+    });
   });
 }
@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use std::collections::HashMap;
 use std::time::{Duration, SystemTime};
 
 use chrono::offset::Utc;
@@ -68,6 +69,38 @@ fn set_seconds_before_unix_epoch(seconds: u64) -> Result<SystemTime> {
         .ok_or(ChronologicalError::TimeOverflow { a, b })
 }
 
+pub struct TimestampHolder {
+    pub created: SystemTime,
+    pub updated: Option<SystemTime>,
+    pub history: Vec<SystemTime>,
+    pub checkpoints: HashMap<String, SystemTime>,
+}
+
+pub enum TimeEvent {
+    Started { at: SystemTime },
+    Finished { at: SystemTime, elapsed: Duration },
+}
+
+pub trait TimestampShifter: Send + Sync {
+    fn shift(&self, at: SystemTime, by: Duration) -> SystemTime;
+}
+
+fn return_holder(holder: TimestampHolder) -> TimestampHolder {
+    holder
+}
+
+fn return_event(event: TimeEvent) -> TimeEvent {
+    event
+}
+
+fn return_timestamps(a: Vec<SystemTime>) -> Vec<SystemTime> {
+    a
+}
+
+fn shift_with(shifter: Box<dyn TimestampShifter>, at: SystemTime, by: Duration) -> SystemTime {
+    shifter.shift(at, by)
+}
+
 type Result<T, E = ChronologicalError> = std::result::Result<T, E>;
 
 uniffi::include_scaffolding!("api");
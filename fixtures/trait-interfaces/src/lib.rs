@@ -93,4 +93,21 @@ impl Registry {
     }
 }
 
+// A trait implemented in Dart, called back synchronously from Rust.
+#[uniffi::export(with_foreign)]
+pub trait Namer: Send + Sync {
+    fn name(&self) -> String;
+}
+
+#[uniffi::export]
+pub fn greet_by_namer(greeter: Arc<dyn Greeter>, namer: Arc<dyn Namer>) -> String {
+    greeter.greet(namer.name())
+}
+
+// Handing a Dart object back to Dart clones its handle.
+#[uniffi::export]
+pub fn echo_namer(namer: Arc<dyn Namer>) -> Arc<dyn Namer> {
+    namer
+}
+
 uniffi::include_scaffolding!("api");
@@ -1,6 +1,14 @@
 import 'package:test/test.dart';
 import '../trait_interfaces.dart';
 
+class DartNamer implements Namer {
+  final String value;
+  DartNamer(this.value);
+
+  @override
+  String name() => value;
+}
+
 void main() {
   group('FriendlyGreeter', () {
     test('toTrait produces a Greeter handle', () {
@@ -38,4 +46,19 @@ void main() {
       proc.dispose();
     });
   });
+
+  group('Namer', () {
+    test('Rust calls back into a Dart implementation', () {
+      final Greeter greeter = Registry().makeFriendly('Hello');
+      expect(greetByNamer(greeter, DartNamer('Dart')), equals('Hello Dart'));
+      greeter.dispose();
+    });
+
+    test('a Dart object handed back by Rust is the same object', () {
+      final namer = DartNamer('echo');
+      final echoed = echoNamer(namer);
+      expect(identical(echoed, namer), isTrue);
+      expect(echoed.name(), equals('echo'));
+    });
+  });
 }
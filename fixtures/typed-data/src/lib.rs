@@ -0,0 +1,105 @@
+#[derive(uniffi::Record)]
+pub struct Signal {
+    pub sample_rate: u32,
+    pub samples: Vec<f32>,
+    pub markers: Vec<u64>,
+}
+
+#[uniffi::export]
+pub fn echo_i8s(values: Vec<i8>) -> Vec<i8> {
+    values
+}
+
+#[uniffi::export]
+pub fn echo_u8s(values: Vec<u8>) -> Vec<u8> {
+    values
+}
+
+#[uniffi::export]
+pub fn echo_i16s(values: Vec<i16>) -> Vec<i16> {
+    values
+}
+
+#[uniffi::export]
+pub fn echo_u16s(values: Vec<u16>) -> Vec<u16> {
+    values
+}
+
+#[uniffi::export]
+pub fn echo_i32s(values: Vec<i32>) -> Vec<i32> {
+    values
+}
+
+#[uniffi::export]
+pub fn echo_u32s(values: Vec<u32>) -> Vec<u32> {
+    values
+}
+
+#[uniffi::export]
+pub fn echo_i64s(values: Vec<i64>) -> Vec<i64> {
+    values
+}
+
+#[uniffi::export]
+pub fn echo_u64s(values: Vec<u64>) -> Vec<u64> {
+    values
+}
+
+#[uniffi::export]
+pub fn echo_f32s(values: Vec<f32>) -> Vec<f32> {
+    values
+}
+
+#[uniffi::export]
+pub fn echo_f64s(values: Vec<f64>) -> Vec<f64> {
+    values
+}
+
+// Checks the values arrive in Rust intact, not just that they round-trip
+#[uniffi::export]
+pub fn sum_i32s(values: Vec<i32>) -> i64 {
+    values.iter().map(|&v| i64::from(v)).sum()
+}
+
+#[uniffi::export]
+pub fn ramp_f64s(len: u32) -> Vec<f64> {
+    (0..len).map(|i| f64::from(i) * 0.5).collect()
+}
+
+#[uniffi::export]
+pub fn scale_signal(signal: Signal, factor: f32) -> Signal {
+    Signal {
+        samples: signal.samples.iter().map(|s| s * factor).collect(),
+        ..signal
+    }
+}
+
+#[uniffi::export]
+pub fn echo_nested(values: Vec<Vec<i16>>) -> Vec<Vec<i16>> {
+    values
+}
+
+#[uniffi::export]
+pub fn echo_optional(values: Option<Vec<u32>>) -> Option<Vec<u32>> {
+    values
+}
+
+#[derive(uniffi::Record)]
+pub struct Histogram {
+    #[uniffi(default)]
+    pub buckets: Vec<u32>,
+    #[uniffi(default = [])]
+    pub weights: Vec<f64>,
+}
+
+#[uniffi::export]
+pub fn echo_histogram(histogram: Histogram) -> Histogram {
+    histogram
+}
+
+#[uniffi::export(default(values = []))]
+pub fn count_i16s(values: Vec<i16>) -> u32 {
+    values.len() as u32
+}
+
+uniffi::include_scaffolding!("api");
@@ -0,0 +1,77 @@
+import 'dart:typed_data';
+
+import 'package:test/test.dart';
+import '../typed_data.dart';
+
+void main() {
+  group('typed data sequences', () {
+    test('every element type round-trips with its byte order intact', () {
+      expect(echoI8s(Int8List.fromList([-128, -1, 0, 127])), equals([-128, -1, 0, 127]));
+      expect(echoU8s(Uint8List.fromList([0, 1, 255])), equals([0, 1, 255]));
+      expect(echoI16s(Int16List.fromList([-32768, -2, 258, 32767])),
+          equals([-32768, -2, 258, 32767]));
+      expect(echoU16s(Uint16List.fromList([0, 258, 65535])), equals([0, 258, 65535]));
+      expect(echoI32s(Int32List.fromList([-2147483648, -1, 16909060, 2147483647])),
+          equals([-2147483648, -1, 16909060, 2147483647]));
+      expect(echoU32s(Uint32List.fromList([0, 16909060, 4294967295])),
+          equals([0, 16909060, 4294967295]));
+      expect(echoI64s(Int64List.fromList([-9223372036854775808, 72623859790382856, 9223372036854775807])),
+          equals([-9223372036854775808, 72623859790382856, 9223372036854775807]));
+      expect(echoU64s(Uint64List.fromList([0, 72623859790382856])), equals([0, 72623859790382856]));
+      expect(echoF32s(Float32List.fromList([-1.5, 0.0, 3.25])), equals([-1.5, 0.0, 3.25]));
+      expect(echoF64s(Float64List.fromList([-1.5, 0.1, double.maxFinite])),
+          equals([-1.5, 0.1, double.maxFinite]));
+    });
+
+    test('values are typed data lists', () {
+      expect(echoI32s(Int32List(0)), isA<Int32List>());
+      expect(echoF64s(Float64List(0)), isA<Float64List>());
+      expect(echoI32s(Int32List(0)), isEmpty);
+    });
+
+    test('Rust sees the same values', () {
+      expect(sumI32s(Int32List.fromList([1, 2, 3, -10])), equals(-4));
+      final ramp = rampF64s(5);
+      expect(ramp, equals([0.0, 0.5, 1.0, 1.5, 2.0]));
+    });
+
+    test('views into larger buffers', () {
+      final backing = Int32List.fromList([9, 1, 2, 3, 9]);
+      final view = Int32List.sublistView(backing, 1, 4);
+      expect(echoI32s(view), equals([1, 2, 3]));
+      expect(sumI32s(view), equals(6));
+    });
+
+    test('million element arrays', () {
+      final values = Float64List(1000000);
+      for (var i = 0; i < values.length; i++) {
+        values[i] = i * 0.25;
+      }
+      final result = echoF64s(values);
+      expect(result.length, equals(values.length));
+      expect(result, equals(values));
+      expect(rampF64s(1000000)[999999], equals(499999.5));
+    });
+
+    test('defaults are empty typed data lists', () {
+      final histogram = Histogram();
+      expect(histogram.buckets, isA<Uint32List>());
+      expect(histogram.buckets, isEmpty);
+      expect(histogram.weights, isA<Float64List>());
+      expect(echoHistogram(histogram), equals(histogram));
+      expect(Histogram(buckets: Uint32List.fromList([3])).buckets, equals([3]));
+      expect(countI16s(), equals(0));
+      expect(countI16s(values: Int16List.fromList([1, 2])), equals(2));
+    });
+
+    test('records, nesting and optionals', () {
+      final signal = Signal(48000, Float32List.fromList([0.5, -0.25]), Uint64List.fromList([1, 2]));
+      expect(scaleSignal(signal, 2.0),
+          equals(Signal(48000, Float32List.fromList([1.0, -0.5]), Uint64List.fromList([1, 2]))));
+      final nested = [Int16List.fromList([1, -1]), Int16List(0)];
+      expect(echoNested(nested), equals(nested));
+      expect(echoOptional(Uint32List.fromList([7])), equals([7]));
+      expect(echoOptional(null), isNull);
+    });
+  });
+}
@@ -0,0 +1,6 @@
+use anyhow::Result;
+
+#[test]
+fn typed_data() -> Result<()> {
+    uniffi_dart::testing::run_test("typed_data", "src/api.udl", Some("uniffi.toml"))
+}
@@ -0,0 +1,32 @@
+#[derive(uniffi::Record)]
+pub struct Packet {
+    pub id: u32,
+    pub payload: Vec<u8>,
+}
+
+#[uniffi::export]
+pub fn echo_bytes(value: Vec<u8>) -> Vec<u8> {
+    value
+}
+
+#[uniffi::export]
+pub fn filled_bytes(len: u32, fill: u8) -> Vec<u8> {
+    vec![fill; len as usize]
+}
+
+#[uniffi::export]
+pub fn checksum(value: Vec<u8>) -> u64 {
+    value.iter().map(|&b| u64::from(b)).sum()
+}
+
+#[uniffi::export]
+pub fn echo_packet(packet: Packet) -> Packet {
+    packet
+}
+
+#[uniffi::export]
+pub fn echo_optional_bytes(value: Option<Vec<u8>>) -> Option<Vec<u8>> {
+    value
+}
+
+uniffi::include_scaffolding!("api");
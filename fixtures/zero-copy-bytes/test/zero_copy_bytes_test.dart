@@ -0,0 +1,47 @@
+import 'dart:typed_data';
+
+import 'package:test/test.dart';
+import '../zero_copy_bytes.dart';
+
+void main() {
+  group('zero-copy bytes', () {
+    test('round-trip', () {
+      expect(echoBytes(Uint8List.fromList([0, 1, 2, 255])), equals([0, 1, 2, 255]));
+      expect(echoBytes(Uint8List(0)), isEmpty);
+    });
+
+    test('lifted bytes stay usable and writable', () {
+      final bytes = filledBytes(16, 7);
+      expect(bytes.length, equals(16));
+      expect(bytes.every((b) => b == 7), isTrue);
+      bytes[0] = 9;
+      expect(checksum(bytes), equals(9 + 15 * 7));
+    });
+
+    test('views into larger lists are lowered from their own range', () {
+      final backing = Uint8List.fromList([1, 2, 3, 4, 5]);
+      expect(echoBytes(Uint8List.sublistView(backing, 1, 4)), equals([2, 3, 4]));
+    });
+
+    test('large buffers', () {
+      final bytes = filledBytes(1 << 22, 1);
+      expect(bytes.length, equals(1 << 22));
+      expect(checksum(bytes), equals(1 << 22));
+      expect(echoBytes(bytes).length, equals(1 << 22));
+    });
+
+    test('many short-lived buffers are released', () {
+      // Each lifted list frees its buffer once collected; this would exhaust memory otherwise
+      for (var i = 0; i < 2000; i++) {
+        expect(filledBytes(1 << 16, i % 256)[0], equals(i % 256));
+      }
+    });
+
+    test('inside records and optionals', () {
+      final packet = Packet(1, Uint8List.fromList([4, 5, 6]));
+      expect(echoPacket(packet), equals(packet));
+      expect(echoOptionalBytes(Uint8List.fromList([1])), equals([1]));
+      expect(echoOptionalBytes(null), isNull);
+    });
+  });
+}
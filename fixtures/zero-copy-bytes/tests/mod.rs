@@ -0,0 +1,6 @@
+use anyhow::Result;
+
+#[test]
+fn zero_copy_bytes() -> Result<()> {
+    uniffi_dart::testing::run_test("zero_copy_bytes", "src/api.udl", Some("uniffi.toml"))
+}
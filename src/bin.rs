@@ -1,4 +1,81 @@
-fn main() {
-    println!("uniffi_bindgen_dart binary");
-    println!("This binary is a placeholder and will be implemented in the future.");
+use anyhow::{bail, Result};
+use camino::Utf8PathBuf;
+use clap::{Parser, Subcommand};
+use uniffi_dart::gen;
+
+#[derive(Parser)]
+#[command(
+    name = "uniffi-bindgen-dart",
+    version,
+    about = "Dart frontend for UniFFI"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Generate Dart bindings for a UniFFI component
+    Generate {
+        /// Path to the UDL file. May be omitted when `--library` is given.
+        udl_file: Option<Utf8PathBuf>,
+
+        /// Path to the compiled cdylib. Without a UDL file, bindings are
+        /// generated from the metadata embedded in the library.
+        #[arg(long, value_name = "CDYLIB")]
+        library: Option<Utf8PathBuf>,
+
+        /// Path to an optional `uniffi.toml` overriding the crate config
+        #[arg(long, short)]
+        config: Option<Utf8PathBuf>,
+
+        /// Directory the `.dart` files are written to
+        #[arg(long, short)]
+        out_dir: Option<Utf8PathBuf>,
+
+        /// Only generate bindings for this crate
+        #[arg(long = "crate", value_name = "NAME")]
+        crate_name: Option<String>,
+
+        /// Skip running `dart format` on the generated files
+        #[arg(long)]
+        no_format: bool,
+    },
+}
+
+fn main() -> Result<()> {
+    match Cli::parse().command {
+        Commands::Generate {
+            udl_file,
+            library,
+            config,
+            out_dir,
+            crate_name,
+            no_format,
+        } => match (udl_file, library) {
+            (Some(udl_file), library) => gen::generate_dart_bindings_from_udl(
+                &udl_file,
+                config.as_deref(),
+                out_dir.as_deref(),
+                library.as_deref(),
+                crate_name.as_deref(),
+                !no_format,
+            ),
+            (None, Some(library)) => {
+                let Some(out_dir) = out_dir else {
+                    bail!("--out-dir is required when generating from a library");
+                };
+                gen::generate_dart_bindings_from_library(
+                    &library,
+                    crate_name,
+                    config.as_deref(),
+                    &out_dir,
+                    !no_format,
+                )?;
+                Ok(())
+            }
+            (None, None) => bail!("either a UDL file or --library <CDYLIB> is required"),
+        },
+    }
 }
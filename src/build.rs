@@ -1,18 +1,58 @@
 use anyhow::{Context, Result};
 use camino::Utf8Path;
 use std::env;
+use std::fs;
+use std::io::Write;
+use uniffi_bindgen::interface::ComponentInterface;
 
 pub fn generate_scaffolding(udl_file: &Utf8Path) -> Result<()> {
     uniffi_build::generate_scaffolding(udl_file)?;
     let out_dir = env::var("OUT_DIR").context("$OUT_DIR missing?!")?;
-    uniffi_bindgen::generate_external_bindings(
-        &crate::gen::DartBindingGenerator {},
+    append_rustbuffer_finalizer(udl_file, &out_dir)?;
+    crate::gen::generate_dart_bindings_from_udl(
         udl_file,
-        None::<&Utf8Path>,
-        Some(out_dir),
-        None::<&Utf8Path>,
+        None,
+        Some(Utf8Path::new(&out_dir)),
+        None,
         None,
         true,
     )?;
     Ok(())
 }
+
+// `NativeFinalizer`s can only call native functions taking a single pointer, while
+// `ffi_*_rustbuffer_free` takes the `RustBuffer` itself, so zero-copy bytes box the buffer and
+// free it through these instead.
+fn append_rustbuffer_finalizer(udl_file: &Utf8Path, out_dir: &str) -> Result<()> {
+    let udl = fs::read_to_string(udl_file)?;
+    let namespace = ComponentInterface::from_webidl(&udl, "crate_name")?
+        .namespace()
+        .to_string();
+    let stem = udl_file.file_stem().context("UDL file has no name")?;
+    let mut scaffolding = fs::OpenOptions::new()
+        .append(true)
+        .open(Utf8Path::new(out_dir).join(format!("{stem}.uniffi.rs")))?;
+    write!(
+        scaffolding,
+        r#"
+/// Moves `buf` to the heap, returning a token to pass to `ffi_{namespace}_dart_rustbuffer_finalize`.
+#[doc(hidden)]
+#[unsafe(no_mangle)]
+pub extern "C" fn ffi_{namespace}_dart_rustbuffer_token(buf: ::uniffi::RustBuffer) -> *mut ::std::ffi::c_void {{
+    ::std::boxed::Box::into_raw(::std::boxed::Box::new(buf)).cast()
+}}
+
+/// Frees the `RustBuffer` behind a token from `ffi_{namespace}_dart_rustbuffer_token`.
+///
+/// # Safety
+///
+/// `token` must come from `ffi_{namespace}_dart_rustbuffer_token` and not have been finalized yet.
+#[doc(hidden)]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ffi_{namespace}_dart_rustbuffer_finalize(token: *mut ::std::ffi::c_void) {{
+    unsafe {{ ::std::boxed::Box::from_raw(token.cast::<::uniffi::RustBuffer>()) }}.destroy();
+}}
+"#
+    )?;
+    Ok(())
+}
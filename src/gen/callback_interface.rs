@@ -1,7 +1,10 @@
-use crate::gen::CodeType;
+use std::collections::BTreeMap;
+
+use crate::gen::{CodeType, Config};
 use genco::prelude::*;
-use uniffi_bindgen::interface::Type;
-use uniffi_bindgen::interface::{AsType, Method};
+use heck::ToUpperCamelCase;
+use uniffi_bindgen::interface::{AsType, FfiType, Method, Type};
+use uniffi_bindgen::ComponentInterface;
 
 use crate::gen::oracle::{AsCodeType, DartCodeOracle};
 use crate::gen::render::AsRenderable;
@@ -22,12 +25,15 @@ impl CallbackInterfaceCodeType {
 }
 
 impl CodeType for CallbackInterfaceCodeType {
-    fn type_label(&self) -> String {
+    fn type_label(&self, _config: &Config) -> String {
         super::DartCodeOracle::class_name(&self.name)
     }
 
     fn canonical_name(&self) -> String {
-        format!("CallbackInterface{}", self.type_label())
+        format!(
+            "CallbackInterface{}",
+            super::DartCodeOracle::class_name(&self.name)
+        )
     }
 
     fn initialization_fn(&self) -> Option<String> {
@@ -46,6 +52,7 @@ impl Renderable for CallbackInterfaceCodeType {
         // Generate all necessary components for the callback interface
         let interface = generate_callback_interface(
             callback.name(),
+            callback.docstring(),
             &callback.as_codetype().ffi_converter_name(),
             &callback.methods(),
             type_helper,
@@ -77,6 +84,7 @@ impl Renderable for CallbackInterfaceCodeType {
 
 pub fn generate_callback_interface(
     callback_name: &str,
+    docstring: Option<&str>,
     ffi_converter_name: &str,
     methods: &[&Method],
     type_helper: &dyn TypeHelperRenderer,
@@ -87,6 +95,7 @@ pub fn generate_callback_interface(
 
     let tokens = quote! {
         // This is the abstract class to be implemented
+        $(DartCodeOracle::doc_comment(docstring))
         abstract class $cls_name {
             $(for m in methods {
                 $(generate_callback_methods_definitions(m, type_helper))
@@ -160,8 +169,14 @@ fn generate_callback_methods_definitions(
     } else {
         quote!(void)
     };
+    let ret_type = if method.is_async() {
+        quote!(Future<$ret_type>)
+    } else {
+        ret_type
+    };
 
     quote!(
+        $(DartCodeOracle::doc_comment(method.docstring()))
         $ret_type $method_name($(for a in dart_args => $a,));
     )
 }
@@ -180,6 +195,22 @@ fn generate_callback_methods_signatures(
         let dart_method_type =
             format!("UniffiCallbackInterface{callback_name}Method{method_index}Dart");
 
+        if method.is_async() {
+            let complete_type = format!(
+                "UniffiForeignFutureComplete{}",
+                foreign_future_suffix(method.return_type())
+            );
+            tokens.append(quote! {
+                typedef $ffi_method_type = Void Function(
+                    Uint64, $(for arg in &method.arguments() => $(DartCodeOracle::native_type_label(Some(&arg.as_type()), type_helper.get_ci())),)
+                    Pointer<NativeFunction<$(&complete_type)>>, Uint64, Pointer<UniffiForeignFutureDroppedCallbackStruct>);
+                typedef $dart_method_type = void Function(
+                    int, $(for arg in &method.arguments() => $(DartCodeOracle::native_dart_type_label(Some(&arg.as_type()), type_helper.get_ci())),)
+                    Pointer<NativeFunction<$(&complete_type)>>, int, Pointer<UniffiForeignFutureDroppedCallbackStruct>);
+            });
+            continue;
+        }
+
         let method_return_type = if let Some(ret) = method.return_type() {
             DartCodeOracle::native_type_label(Some(ret), type_helper.get_ci())
         } else {
@@ -199,6 +230,8 @@ fn generate_callback_methods_signatures(
     tokens.append(quote! {
         typedef UniffiCallbackInterface$(callback_name)Free = Void Function(Uint64);
         typedef UniffiCallbackInterface$(callback_name)FreeDart = void Function(int);
+        typedef UniffiCallbackInterface$(callback_name)Clone = Uint64 Function(Uint64);
+        typedef UniffiCallbackInterface$(callback_name)CloneDart = int Function(int);
     });
 
     tokens
@@ -213,10 +246,11 @@ pub fn generate_callback_vtable_interface(
 
     quote! {
         final class $vtable_name extends Struct {
+            external Pointer<NativeFunction<UniffiCallbackInterface$(callback_name)Free>> uniffiFree;
+            external Pointer<NativeFunction<UniffiCallbackInterface$(callback_name)Clone>> uniffiClone;
             $(for (index, m) in &methods_vec =>
                 external Pointer<NativeFunction<UniffiCallbackInterface$(callback_name)Method$(format!("{}",index))>> $(DartCodeOracle::fn_name(m.name()));
             )
-            external Pointer<NativeFunction<UniffiCallbackInterface$(callback_name)Free>> uniffiFree;
         }
     }
 }
@@ -250,6 +284,19 @@ pub fn generate_callback_functions(
             DartCodeOracle::callback_arg_name(&arg.as_type(), arg_idx)
         }).collect();
 
+        let callback_method_name = &format!("{}{}", &DartCodeOracle::fn_name(callback_name), &DartCodeOracle::class_name(m.name()));
+
+        if m.is_async() {
+            return generate_async_callback_function(
+                m,
+                cls_name,
+                callback_method_name,
+                ffi_method_type,
+                CallbackArguments { param_types, arg_lifts, arg_names },
+                type_helper,
+            );
+        }
+
         // Handle return value using the oracle
         let call_dart_method = if let Some(ret) = m.return_type() {
             DartCodeOracle::callback_return_handling(ret, method_name, arg_names)
@@ -262,7 +309,6 @@ pub fn generate_callback_functions(
         let out_return_type = DartCodeOracle::callback_out_return_type(m.return_type());
 
         // Generate the function body
-        let callback_method_name = &format!("{}{}", &DartCodeOracle::fn_name(callback_name), &DartCodeOracle::class_name(m.name()));
 
         quote! {
             void $callback_method_name(int uniffiHandle, $(for param in &param_types => $param,) $out_return_type outReturn, Pointer<RustCallStatus> callStatus) {
@@ -287,6 +333,11 @@ pub fn generate_callback_functions(
     let free_callback_pointer = &format!("{}FreePointer", DartCodeOracle::fn_name(callback_name));
     let free_callback_type = &format!("UniffiCallbackInterface{callback_name}Free");
 
+    // Clone callback
+    let clone_callback_fn = &format!("{}CloneCallback", DartCodeOracle::fn_name(callback_name));
+    let clone_callback_pointer = &format!("{}ClonePointer", DartCodeOracle::fn_name(callback_name));
+    let clone_callback_type = &format!("UniffiCallbackInterface{callback_name}Clone");
+
     quote! {
         $(functions)
 
@@ -300,6 +351,211 @@ pub fn generate_callback_functions(
 
         final Pointer<NativeFunction<$free_callback_type>> $free_callback_pointer =
             Pointer.fromFunction<$free_callback_type>($free_callback_fn);
+
+        // Rust clones a handle when it hands the object back to Dart, so each side can free its own.
+        // Cloning can't fail over FFI, so a stale handle is reported as an uncaught error and
+        // Rust gets the invalid handle 0, which every later call rejects.
+        int $clone_callback_fn(int handle) {
+            try {
+                final obj = FfiConverterCallbackInterface$cls_name._handleMap.get(handle);
+                return FfiConverterCallbackInterface$cls_name._handleMap.insert(obj);
+            } catch (e, stackTrace) {
+                Zone.current.handleUncaughtError(e, stackTrace);
+                return 0;
+            }
+        }
+
+        final Pointer<NativeFunction<$clone_callback_type>> $clone_callback_pointer =
+            Pointer.fromFunction<$clone_callback_type>($clone_callback_fn, 0);
+    }
+}
+
+struct CallbackArguments {
+    param_types: Vec<dart::Tokens>,
+    arg_lifts: Vec<dart::Tokens>,
+    arg_names: Vec<dart::Tokens>,
+}
+
+// Async methods hand their outcome to the completion callback Rust passes in, instead of
+// writing it to out parameters before returning.
+fn generate_async_callback_function(
+    method: &Method,
+    cls_name: &str,
+    callback_method_name: &str,
+    ffi_method_type: &str,
+    arguments: CallbackArguments,
+    type_helper: &dyn TypeHelperRenderer,
+) -> dart::Tokens {
+    let method_name = DartCodeOracle::fn_name(method.name());
+    let CallbackArguments {
+        param_types,
+        arg_lifts,
+        arg_names,
+    } = arguments;
+    let suffix = foreign_future_suffix(method.return_type());
+    let complete_type = &format!("UniffiForeignFutureComplete{suffix}");
+    let complete_fn = &format!("uniffiCompleteForeignFuture{suffix}");
+
+    let (dart_ret_type, set_return_value) = match method.return_type() {
+        Some(ret) => {
+            let lowered = DartCodeOracle::type_lower_fn(ret, quote!(value));
+            let lowered = match DartCodeOracle::unwrap_custom(ret) {
+                // Handles complete the future as plain integers
+                Type::Object { .. } => quote!($lowered.address),
+                _ => lowered,
+            };
+            (
+                ret.as_renderable().render_type(ret, type_helper),
+                quote!(result.returnValue = $lowered;),
+            )
+        }
+        None => (quote!(void), quote!()),
+    };
+
+    // Only the method's declared error reaches Rust as a typed error. Object errors are rejected
+    // by `DartCodeOracle::check_async_callback_errors`.
+    let lower_error = match method.throws_type() {
+        Some(error_type @ Type::Enum { .. }) => {
+            let error_codetype = error_type.as_codetype();
+            quote!((e) => e is $(error_codetype.type_label(type_helper.get_config())) ? $(error_codetype.ffi_converter_name()).lower(e) : null,)
+        }
+        _ => quote!(),
+    };
+
+    quote! {
+        void $callback_method_name(int uniffiHandle, $(for param in &param_types => $param,) Pointer<NativeFunction<$complete_type>> uniffiFutureCallback, int uniffiCallbackData, Pointer<UniffiForeignFutureDroppedCallbackStruct> uniffiOutDroppedCallback) {
+            uniffiTraitInterfaceCallAsync<$dart_ret_type>(
+                () {
+                    final obj = FfiConverterCallbackInterface$cls_name._handleMap.get(uniffiHandle);
+                    $(arg_lifts)
+                    return obj.$method_name($(for arg in &arg_names => $arg,));
+                },
+                (value) => $complete_fn(uniffiFutureCallback, uniffiCallbackData, (result) {
+                    $set_return_value
+                }),
+                (code, errorBuf) => $complete_fn(uniffiFutureCallback, uniffiCallbackData, (result) {
+                    result.callStatus.code = code;
+                    result.callStatus.errorBuf = errorBuf;
+                }),
+                $lower_error
+            );
+        }
+
+        // Rust may start the call from any thread, so it is delivered through the event loop.
+        // By then `uniffiOutDroppedCallback` may be gone, so it is left at Rust's no-op default.
+        final Pointer<NativeFunction<$ffi_method_type>> $(callback_method_name)Pointer =
+            (NativeCallable<$ffi_method_type>.listener($callback_method_name)..keepIsolateAlive = false).nativeFunction;
+    }
+}
+
+/// The FFI type an async callback method completes its future with. Like UniFFI, handles share
+/// the `u64` result and every `RustBuffer` shares one, whichever crate it comes from.
+fn foreign_future_ffi_type(return_type: Option<&Type>) -> Option<FfiType> {
+    return_type.map(|ty| match FfiType::from(ty) {
+        FfiType::Handle => FfiType::UInt64,
+        FfiType::RustBuffer(_) => FfiType::RustBuffer(None),
+        ffi_type => ffi_type,
+    })
+}
+
+/// The suffix UniFFI gives the result struct and completion callback for a return type.
+fn foreign_future_suffix(return_type: Option<&Type>) -> String {
+    FfiType::return_type_name(foreign_future_ffi_type(return_type).as_ref()).to_upper_camel_case()
+}
+
+/// The result structs and completion helpers for the return types of every async callback
+/// method, along with the runtime that drives those methods. Empty when there are none.
+pub fn generate_foreign_future_definitions(ci: &ComponentInterface) -> dart::Tokens {
+    let callback_methods = ci
+        .callback_interface_definitions()
+        .iter()
+        .flat_map(|callback| callback.methods());
+    let trait_methods = ci
+        .object_definitions()
+        .iter()
+        .filter(|obj| obj.has_callback_interface())
+        .flat_map(|obj| obj.methods());
+    let return_types = callback_methods
+        .chain(trait_methods)
+        .filter(|method| method.is_async())
+        .map(|method| {
+            (
+                foreign_future_suffix(method.return_type()),
+                foreign_future_ffi_type(method.return_type()),
+            )
+        })
+        .collect::<BTreeMap<_, _>>();
+    if return_types.is_empty() {
+        return quote!();
+    }
+
+    let results = return_types.iter().map(|(suffix, ffi_type)| {
+        let result_struct = &format!("UniffiForeignFutureResult{suffix}");
+        let complete_type = &format!("UniffiForeignFutureComplete{suffix}");
+        let return_value = match ffi_type {
+            Some(FfiType::RustBuffer(_)) => quote!(external RustBuffer returnValue;),
+            Some(ffi_type) => quote!(
+                @$(DartCodeOracle::ffi_native_type_label(Some(ffi_type), ci))()
+                external $(DartCodeOracle::ffi_dart_type_label(Some(ffi_type), ci)) returnValue;
+            ),
+            None => quote!(),
+        };
+
+        quote! {
+            final class $result_struct extends Struct {
+                $return_value
+                external RustCallStatus callStatus;
+            }
+
+            typedef $complete_type = Void Function(Uint64, $result_struct);
+            typedef $(complete_type)Dart = void Function(int, $result_struct);
+
+            void uniffiCompleteForeignFuture$(suffix)(
+                Pointer<NativeFunction<$complete_type>> callback,
+                int callbackData,
+                void Function($result_struct) fill,
+            ) {
+                final result = calloc<$result_struct>();
+                try {
+                    fill(result.ref);
+                    callback.asFunction<$(complete_type)Dart>()(callbackData, result.ref);
+                } finally {
+                    calloc.free(result);
+                }
+            }
+        }
+    });
+
+    quote! {
+        typedef UniffiForeignFutureDroppedCallback = Void Function(Uint64);
+
+        final class UniffiForeignFutureDroppedCallbackStruct extends Struct {
+            @Uint64()
+            external int handle;
+
+            external Pointer<NativeFunction<UniffiForeignFutureDroppedCallback>> free;
+        }
+
+        // Runs the Dart implementation of an async callback method, then hands its result to
+        // `handleSuccess`, or the status code and error buffer it failed with to `handleError`.
+        // Exceptions `lowerError` recognises become typed errors, and any other is unexpected.
+        void uniffiTraitInterfaceCallAsync<T>(
+            Future<T> Function() makeCall,
+            void Function(T) handleSuccess,
+            void Function(int, RustBuffer) handleError, [
+            RustBuffer? Function(Object)? lowerError,
+        ]) {
+            Future.sync(makeCall).then(handleSuccess).catchError((Object e) {
+                final errorBuf = lowerError?.call(e);
+                if (errorBuf != null) {
+                    handleError(CALL_ERROR, errorBuf);
+                } else {
+                    handleError(CALL_UNEXPECTED_ERROR, FfiConverterString.lower(e.toString()));
+                }
+            });
+        }
+
+        $(for result in results => $result)
     }
 }
 
@@ -324,10 +580,11 @@ pub fn generate_callback_interface_vtable_init_function(
             }
 
             $(&vtable_static_instance_name) = calloc<$vtable_name>();
+            $(&vtable_static_instance_name).ref.uniffiFree = $(format!("{}FreePointer", DartCodeOracle::fn_name(callback_name)));
+            $(&vtable_static_instance_name).ref.uniffiClone = $(format!("{}ClonePointer", DartCodeOracle::fn_name(callback_name)));
             $(for m in methods {
                 $(&vtable_static_instance_name).ref.$(DartCodeOracle::fn_name(m.name())) = $(DartCodeOracle::fn_name(callback_name))$(DartCodeOracle::class_name(m.name()))Pointer;
             })
-            $(&vtable_static_instance_name).ref.uniffiFree = $(format!("{}FreePointer", DartCodeOracle::fn_name(callback_name)));
 
             rustCall((status) {
                 _UniffiLib.instance.uniffi_$(ffi_module)_fn_init_callback_vtable_$(snake_callback)(
@@ -1,23 +1,62 @@
+use anyhow::{bail, Result};
 use std::fmt::Debug;
-use uniffi_bindgen::pipeline::general::nodes::Literal;
+use uniffi_bindgen::interface::Literal;
+
+use super::Config;
+
+/// The Dart rendering of a field or argument's default value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DartDefault {
+    /// A constant expression, which can be the parameter's default.
+    Const(String),
+    /// An expression for types without constant values, e.g. `Uint8List(0)`. The parameter is
+    /// made nullable instead, and `null` is replaced with the expression when used.
+    Runtime(String),
+}
+
+impl DartDefault {
+    pub fn expr(&self) -> &str {
+        match self {
+            DartDefault::Const(expr) | DartDefault::Runtime(expr) => expr,
+        }
+    }
+
+    pub fn is_const(&self) -> bool {
+        matches!(self, DartDefault::Const(_))
+    }
+
+    /// Wrap the expression in `f`, keeping it constant if it was.
+    pub fn map(self, f: impl FnOnce(String) -> String) -> Self {
+        match self {
+            DartDefault::Const(expr) => DartDefault::Const(f(expr)),
+            DartDefault::Runtime(expr) => DartDefault::Runtime(f(expr)),
+        }
+    }
+}
 
 /// A trait tor the implementation.
 pub trait CodeType: Debug {
     /// The language specific label used to reference this type. This will be used in
     /// method signatures and property declarations.
-    fn type_label(&self) -> String;
+    ///
+    /// Some types are rendered differently depending on the `config` of the bindings.
+    fn type_label(&self, config: &Config) -> String;
 
     /// A representation of this type label that can be used as part of another
     /// identifier. e.g. `read_foo()`, or `FooInternals`.
     ///
     /// This is especially useful when creating specialized objects or methods to deal
     /// with this type only.
-    fn canonical_name(&self) -> String {
-        self.type_label()
+    fn canonical_name(&self) -> String;
+
+    fn literal(&self, literal: &Literal, _config: &Config) -> Result<DartDefault> {
+        bail!("{} has no literal {literal:?}", self.canonical_name())
     }
 
-    fn literal(&self, _literal: &Literal) -> String {
-        unimplemented!("Unimplemented for {}", self.type_label())
+    /// The type's default, used for fields and arguments declared with a bare
+    /// `#[uniffi(default)]`.
+    fn default_value(&self, _config: &Config) -> Result<DartDefault> {
+        bail!("{} has no default value", self.canonical_name())
     }
 
     /// Name of the FfiConverter
@@ -1,11 +1,50 @@
-use crate::gen::CodeType;
+use crate::gen::{CodeType, DartDefault};
+use anyhow::{bail, Result};
 use genco::lang::dart;
 use genco::prelude::*;
 use paste::paste;
-use uniffi_bindgen::interface::Type;
+use uniffi_bindgen::interface::{Literal, Type};
 
 use super::oracle::{AsCodeType, DartCodeOracle};
 use crate::gen::render::{AsRenderable, Renderable, TypeHelperRenderer};
+use crate::gen::Config;
+
+/// The `dart:typed_data` list a sequence of numbers is exposed as, with the size of its elements.
+///
+/// Their elements are copied in bulk rather than read one by one. `u64`s exposed as `BigInt`
+/// stay a `List`.
+pub fn typed_data_list(ty: &Type, config: &Config) -> Option<(&'static str, usize)> {
+    let Type::Sequence { inner_type } = ty else {
+        return None;
+    };
+    if !config.typed_data_sequences() {
+        return None;
+    }
+    match **inner_type {
+        Type::Int8 => Some(("Int8List", 1)),
+        Type::UInt8 => Some(("Uint8List", 1)),
+        Type::Int16 => Some(("Int16List", 2)),
+        Type::UInt16 => Some(("Uint16List", 2)),
+        Type::Int32 => Some(("Int32List", 4)),
+        Type::UInt32 => Some(("Uint32List", 4)),
+        Type::Int64 => Some(("Int64List", 8)),
+        Type::UInt64 if !config.uint64_as_bigint() => Some(("Uint64List", 8)),
+        Type::Float32 => Some(("Float32List", 4)),
+        Type::Float64 => Some(("Float64List", 8)),
+        _ => None,
+    }
+}
+
+/// Whether `ty` is an optional directly holding another optional, rendered as `Optional<T?>`
+/// because Dart collapses `T??` into `T?`.
+pub fn is_nested_optional(ty: &Type, config: &Config) -> bool {
+    match ty {
+        Type::Optional { inner_type } => {
+            config.nested_optionals() && matches!(**inner_type, Type::Optional { .. })
+        }
+        _ => false,
+    }
+}
 
 macro_rules! impl_code_type_for_compound {
      ($T:ty, $type_label_pattern:literal, $canonical_name_pattern: literal) => {
@@ -26,13 +65,53 @@ macro_rules! impl_code_type_for_compound {
             }
 
             impl CodeType for $T  {
-                fn type_label(&self) -> String {
-                    format!($type_label_pattern, DartCodeOracle::find(self.inner()).type_label())
+                fn type_label(&self, config: &Config) -> String {
+                    let inner = DartCodeOracle::find(self.inner()).type_label(config);
+                    if let Some((list, _)) = typed_data_list(&self.self_type, config) {
+                        list.into()
+                    } else if is_nested_optional(&self.self_type, config) {
+                        format!("Optional<{inner}>")
+                    } else {
+                        format!($type_label_pattern, inner)
+                    }
                 }
 
                 fn canonical_name(&self) -> String {
                     format!($canonical_name_pattern, DartCodeOracle::find(self.inner()).canonical_name())
                 }
+
+                fn literal(&self, literal: &Literal, config: &Config) -> Result<DartDefault> {
+                    let nested = is_nested_optional(&self.self_type, config);
+                    Ok(match literal {
+                        Literal::None if nested => DartDefault::Const("const None()".into()),
+                        Literal::None => DartDefault::Const("null".into()),
+                        Literal::EmptySequence => match typed_data_list(&self.self_type, config) {
+                            Some((list, _)) => DartDefault::Runtime(format!("{list}(0)")),
+                            None => DartDefault::Const("const []".into()),
+                        },
+                        Literal::Some { inner } => {
+                            let inner = DartCodeOracle::default_value(self.inner(), inner, config)?;
+                            match inner {
+                                DartDefault::Const(inner) if nested => DartDefault::Const(format!("const Some({inner})")),
+                                inner if nested => inner.map(|inner| format!("Some({inner})")),
+                                inner => inner,
+                            }
+                        }
+                        _ => DartCodeOracle::find(self.inner()).literal(literal, config)?,
+                    })
+                }
+
+                fn default_value(&self, config: &Config) -> Result<DartDefault> {
+                    // Typed data lists have no constant values, so they are created when used
+                    if let Some((list, _)) = typed_data_list(&self.self_type, config) {
+                        return Ok(DartDefault::Runtime(format!("{list}(0)")));
+                    }
+                    Ok(DartDefault::Const(match self.self_type {
+                        Type::Sequence { .. } => "const []".into(),
+                        _ if is_nested_optional(&self.self_type, config) => "const None()".into(),
+                        _ => "null".into(),
+                    }))
+                }
             }
         }
     }
@@ -57,16 +136,44 @@ macro_rules! impl_renderable_for_compound {
                                     &self.inner(),
                                 ));
 
-                    let raw_type_label = inner_codetype.type_label();
+                    let raw_type_label = inner_codetype.type_label(type_helper.get_config());
                     let inner_type_label =
                         DartCodeOracle::exception_safe_name(&raw_type_label);
 
                     let cl_name_buf =
                         format!($canonical_name_pattern, canonical_with_exception.as_str());
                     let cl_name = &cl_name_buf;
-                    let type_label_buf =
-                        format!($type_label_pattern, inner_type_label.as_str());
+                    // A nested optional is wrapped in `Optional`, so `Some(None)` is `Some(null)`
+                    // rather than collapsing into `null` like `None`.
+                    let nested = is_nested_optional(&self.self_type, type_helper.get_config());
+                    let type_label_buf = if nested {
+                        format!("Optional<{inner_type_label}>")
+                    } else {
+                        format!($type_label_pattern, inner_type_label.as_str())
+                    };
                     let type_label = &type_label_buf;
+                    let some_type_buf = format!("Some<{inner_type_label}>");
+                    let some_type = &some_type_buf;
+                    let (none_value, is_none, some_value, lifted_some, size_param) = if nested {
+                        (
+                            quote!(const None()),
+                            quote!(value is! $some_type),
+                            quote!(value.value),
+                            quote!(Some(result.value)),
+                            quote!($type_label value = const None()),
+                        )
+                    } else {
+                        (
+                            quote!(null),
+                            quote!(value == null),
+                            quote!(value),
+                            quote!(result.value),
+                            quote!($type_label value),
+                        )
+                    };
+                    let none_value = &none_value;
+                    let is_none = &is_none;
+                    let some_value = &some_value;
 
                     let raw_converter_name = inner_codetype.ffi_converter_name();
                     let inner_cl_converter_name_buf =
@@ -80,7 +187,7 @@ macro_rules! impl_renderable_for_compound {
                     let _inner_type_signature =
                         if inner_data_type.contains("Float") { "double" } else { "int" };
 
-                    let inner_helper = if matches!(self.inner(), Type::Sequence { .. }) && !inner_already_registered {
+                    let inner_helper = if matches!(self.inner(), Type::Sequence { .. } | Type::Optional { .. }) && !inner_already_registered {
                         self.inner().as_renderable().render_type_helper(type_helper)
                     } else {
                         quote!()
@@ -90,52 +197,42 @@ macro_rules! impl_renderable_for_compound {
                         class $cl_name {
 
                             static $type_label lift( RustBuffer buf) {
-                                return $cl_name.read(buf.asUint8List()).value;
+                                return uniffiLiftRustBuffer(buf, $cl_name.read);
                             }
 
                             static LiftRetVal<$type_label> read( Uint8List buf) {
-                                if (ByteData.view(buf.buffer, buf.offsetInBytes).getInt8(0) == 0){
-                                    return LiftRetVal(null, 1);
+                                final tag = ByteData.view(buf.buffer, buf.offsetInBytes).getInt8(0);
+                                if (tag == 0){
+                                    return LiftRetVal($none_value, 1);
+                                }
+                                if (tag != 1) {
+                                    throw UniffiInternalError(UniffiInternalError.unexpectedOptionalTag, null);
                                 }
                                 final result = $inner_cl_converter_name.read(Uint8List.view(buf.buffer, buf.offsetInBytes + 1));
-                                return LiftRetVal<$type_label>(result.value, result.bytesRead + 1);
+                                return LiftRetVal<$type_label>($lifted_some, result.bytesRead + 1);
                             }
 
 
-                            static int allocationSize([$type_label value]) {
-                                if (value == null) {
+                            static int allocationSize([$size_param]) {
+                                if ($is_none) {
                                     return 1;
                                 }
-                                return $inner_cl_converter_name.allocationSize(value) + 1;
+                                return $inner_cl_converter_name.allocationSize($some_value) + 1;
                             }
 
                             static RustBuffer lower( $type_label value) {
-                                if (value == null) {
-                                    return toRustBuffer(Uint8List.fromList([0]));
-                                }
-
-                                final length = $cl_name.allocationSize(value);
-
-                                final Pointer<Uint8> frameData = calloc<Uint8>(length); // Allocate a pointer large enough.
-                                final buf = frameData.asTypedList(length); // Create a list that uses our pointer to copy in the data.
-
-                                $cl_name.write(value, buf);
-
-                                final bytes = calloc<ForeignBytes>();
-                                bytes.ref.len = length;
-                                bytes.ref.data = frameData;
-                                return RustBuffer.fromBytes(bytes.ref);
+                                return uniffiLowerIntoRustBuffer($cl_name.allocationSize(value), (buf) => $cl_name.write(value, buf));
                             }
 
                             static int write( $type_label value, Uint8List buf) {
-                                if (value == null) {
+                                if ($is_none) {
                                     buf[0] = 0;
                                     return 1;
                                 }
                                 // we have a value
                                 buf[0] = 1;
 
-                                return $inner_cl_converter_name.write(value, Uint8List.view(buf.buffer, buf.offsetInBytes + 1)) + 1;
+                                return $inner_cl_converter_name.write($some_value, Uint8List.view(buf.buffer, buf.offsetInBytes + 1)) + 1;
                             }
                         }
                         $inner_helper
@@ -154,6 +251,9 @@ macro_rules! impl_renderable_for_compound {
                     if type_helper.include_once_check(&converter_name, &self.self_type) {
                         return quote!();
                     }
+                    if let Some((list, width)) = typed_data_list(&self.self_type, type_helper.get_config()) {
+                        return render_typed_data_sequence(&converter_name, list, width);
+                    }
                     let inner_codetype = self.inner().as_codetype();
 
                     let original_canonical = inner_codetype.canonical_name();
@@ -164,7 +264,7 @@ macro_rules! impl_renderable_for_compound {
                         type_helper.include_once_check(&canonical_with_exception, &self.inner());
                     }
 
-                    let raw_type_label = inner_codetype.type_label();
+                    let raw_type_label = inner_codetype.type_label(type_helper.get_config());
                     let inner_type_label =
                         DartCodeOracle::exception_safe_name(&raw_type_label);
 
@@ -189,7 +289,7 @@ macro_rules! impl_renderable_for_compound {
                         class $cl_name {
 
                             static $type_label lift( RustBuffer buf) {
-                                return $cl_name.read(buf.asUint8List()).value;
+                                return uniffiLiftRustBuffer(buf, $cl_name.read);
                             }
 
                             static LiftRetVal<$type_label> read( Uint8List buf) {
@@ -217,9 +317,7 @@ macro_rules! impl_renderable_for_compound {
                             }
 
                             static RustBuffer lower( $type_label value) {
-                                final buf = Uint8List(allocationSize(value));
-                                write(value, buf);
-                                return toRustBuffer(buf);
+                                return uniffiLowerIntoRustBuffer(allocationSize(value), (buf) => write(value, buf));
                             }
                         }
                     }
@@ -229,6 +327,46 @@ macro_rules! impl_renderable_for_compound {
    }
 }
 
+// The elements are big-endian on the wire and in host order in the list, so after the bulk copy
+// their bytes are swapped in place where the two differ.
+fn render_typed_data_sequence(cl_name: &str, list: &str, width: usize) -> dart::Tokens {
+    quote! {
+        class $cl_name {
+
+            static $list lift( RustBuffer buf) {
+                return uniffiLiftRustBuffer(buf, $cl_name.read);
+            }
+
+            static LiftRetVal<$list> read( Uint8List buf) {
+                final length = buf.buffer.asByteData(buf.offsetInBytes).getInt32(0);
+                final size = length * $width;
+                final res = $list(length);
+                final bytes = res.buffer.asUint8List();
+                bytes.setRange(0, size, buf, 4);
+                uniffiSwapHostByteOrder(bytes, $width);
+                return LiftRetVal(res, size + 4);
+            }
+
+            static int write( $list value, Uint8List buf) {
+                buf.buffer.asByteData(buf.offsetInBytes).setInt32(0, value.length);
+                final size = value.lengthInBytes;
+                final bytes = Uint8List.sublistView(buf, 4, size + 4);
+                bytes.setRange(0, size, value.buffer.asUint8List(value.offsetInBytes, size));
+                uniffiSwapHostByteOrder(bytes, $width);
+                return size + 4;
+            }
+
+            static int allocationSize($list value) {
+                return value.lengthInBytes + 4;
+            }
+
+            static RustBuffer lower( $list value) {
+                return uniffiLowerIntoRustBuffer(allocationSize(value), (buf) => write(value, buf));
+            }
+        }
+    }
+}
+
 impl_code_type_for_compound!(OptionalCodeType, "{}?", "Optional{}");
 impl_code_type_for_compound!(SequenceCodeType, "List<{}>", "Sequence{}");
 
@@ -262,11 +400,11 @@ impl MapCodeType {
 }
 
 impl CodeType for MapCodeType {
-    fn type_label(&self) -> String {
+    fn type_label(&self, config: &Config) -> String {
         format!(
             "Map<{}, {}>",
-            DartCodeOracle::find(self.key()).type_label(),
-            DartCodeOracle::find(self.value()).type_label()
+            DartCodeOracle::find(self.key()).type_label(config),
+            DartCodeOracle::find(self.value()).type_label(config)
         )
     }
 
@@ -275,6 +413,17 @@ impl CodeType for MapCodeType {
         let val = DartCodeOracle::find(self.value()).canonical_name();
         format!("Map{}To{}", key, val)
     }
+
+    fn literal(&self, literal: &Literal, _config: &Config) -> Result<DartDefault> {
+        match literal {
+            Literal::EmptyMap => Ok(DartDefault::Const("const {}".into())),
+            _ => bail!("Only empty map literals are supported, not {literal:?}"),
+        }
+    }
+
+    fn default_value(&self, _config: &Config) -> Result<DartDefault> {
+        Ok(DartDefault::Const("const {}".into()))
+    }
 }
 
 impl Renderable for MapCodeType {
@@ -288,8 +437,8 @@ impl Renderable for MapCodeType {
         type_helper.include_once_check(&val_codetype.canonical_name(), self.value());
 
         let cl_name = &self.ffi_converter_name();
-        let key_type_label_owned = key_codetype.type_label();
-        let val_type_label_owned = val_codetype.type_label();
+        let key_type_label_owned = key_codetype.type_label(type_helper.get_config());
+        let val_type_label_owned = val_codetype.type_label(type_helper.get_config());
         let key_type_label = &key_type_label_owned;
         let val_type_label = &val_type_label_owned;
 
@@ -301,7 +450,7 @@ impl Renderable for MapCodeType {
         quote! {
             class $cl_name {
                 static Map<$key_type_label, $val_type_label> lift(RustBuffer buf) {
-                    return $cl_name.read(buf.asUint8List()).value;
+                    return uniffiLiftRustBuffer(buf, $cl_name.read);
                 }
 
                 static LiftRetVal<Map<$key_type_label, $val_type_label>> read(Uint8List buf) {
@@ -335,9 +484,7 @@ impl Renderable for MapCodeType {
                 }
 
                 static RustBuffer lower(Map<$key_type_label, $val_type_label> value) {
-                    final buf = Uint8List(allocationSize(value));
-                    write(value, buf);
-                    return toRustBuffer(buf);
+                    return uniffiLowerIntoRustBuffer(allocationSize(value), (buf) => write(value, buf));
                 }
             }
         }
@@ -1,9 +1,10 @@
 use super::oracle::{AsCodeType, DartCodeOracle};
-use super::render::{Renderable, TypeHelperRenderer};
-use super::CodeType;
+use super::render::{AsRenderable, Renderable, TypeHelperRenderer};
+use super::{CodeType, Config, DartDefault};
+use anyhow::Result;
 use genco::prelude::*;
 use uniffi_bindgen::interface::AsType;
-use uniffi_bindgen::interface::Type;
+use uniffi_bindgen::interface::{FfiType, Literal, Type};
 
 #[derive(Debug)]
 pub struct CustomCodeType {
@@ -23,9 +24,21 @@ impl CustomCodeType {
 }
 
 impl CodeType for CustomCodeType {
-    fn type_label(&self) -> String {
+    fn type_label(&self, _config: &Config) -> String {
         DartCodeOracle::class_name(&self.name)
     }
+
+    fn canonical_name(&self) -> String {
+        DartCodeOracle::class_name(&self.name)
+    }
+
+    fn literal(&self, literal: &Literal, config: &Config) -> Result<DartDefault> {
+        self.builtin.as_codetype().literal(literal, config)
+    }
+
+    fn default_value(&self, config: &Config) -> Result<DartDefault> {
+        self.builtin.as_codetype().default_value(config)
+    }
 }
 
 impl AsType for CustomCodeType {
@@ -42,14 +55,124 @@ impl Renderable for CustomCodeType {
     fn render_type_helper(&self, type_helper: &dyn TypeHelperRenderer) -> dart::Tokens {
         type_helper.include_once_check(&self.name, &self.as_type());
 
+        // Records, enums and objects are rendered from their definitions; any other builtin needs
+        // its converter rendered here unless something else already registered it
+        let builtin_helper = match *self.builtin {
+            Type::Record { .. } | Type::Enum { .. } | Type::Object { .. } => quote!(),
+            _ if type_helper.include_once_check(
+                &self.builtin.as_codetype().canonical_name(),
+                &self.builtin,
+            ) =>
+            {
+                quote!()
+            }
+            _ => self.builtin.as_renderable().render_type_helper(type_helper),
+        };
+
         let ffi_converter_name = &self.ffi_converter_name();
-        let type_name = &self.type_label();
+        let config = type_helper.get_config();
+        let type_name = &self.type_label(config);
         let builtin_ffi_converter_name = &(*self.builtin).as_codetype().ffi_converter_name();
-        let builtin_name = DartCodeOracle::dart_type_label(Some(&*self.builtin));
+        let builtin_name = DartCodeOracle::dart_type_label(Some(&*self.builtin), config);
+
+        let Some(custom) = config.custom_type(&self.name) else {
+            return quote! {
+                $builtin_helper
+
+                typedef $(type_name) = $(&builtin_name);
+                typedef $(ffi_converter_name) = $(builtin_ffi_converter_name);
+            };
+        };
 
+        // The converter wraps the builtin's converter, so containers of the custom type convert
+        // every element through it as well
+        let ffi_type = DartCodeOracle::ffi_dart_type_label(
+            Some(&FfiType::from(&*self.builtin)),
+            type_helper.get_ci(),
+        );
+        // A custom type over `u64` may pick `BigInt` or `int` independently of the builtin, and is
+        // converted between the two around the builtin's converter
+        let bigint = custom
+            .bigint()
+            .filter(|bigint| *self.builtin == Type::UInt64 && *bigint != config.uint64_as_bigint());
+        let (to_custom, to_builtin) = match bigint {
+            Some(true) => ("uniffiUInt64ToBigInt", "uniffiBigIntToUInt64"),
+            Some(false) => ("uniffiBigIntToUInt64", "uniffiUInt64ToBigInt"),
+            None => ("", ""),
+        };
+        let custom_type_name = match (custom.type_name(), bigint) {
+            (Some(custom_type_name), _) => quote!($custom_type_name),
+            (None, Some(true)) => quote!(BigInt),
+            (None, Some(false)) => quote!(int),
+            (None, None) => builtin_name.clone(),
+        };
+        // Extension types wrap the converted value, and are unwrapped through their `value`
+        let extension_type = custom.extension_type();
+        let declaration = if extension_type {
+            quote!(extension type const $(type_name)($custom_type_name value) {})
+        } else {
+            quote!(typedef $(type_name) = $custom_type_name;)
+        };
+        let wrap = |expr: String| {
+            if extension_type {
+                format!("{type_name}({expr})")
+            } else {
+                expr
+            }
+        };
+        let lifted = wrap(custom.lift(&format!(
+            "{to_custom}({builtin_ffi_converter_name}.lift(value))"
+        )));
+        let read_value = wrap(custom.lift(&format!("{to_custom}(builtin.value)")));
+        let unwrapped = if extension_type {
+            "value.value"
+        } else {
+            "value"
+        };
+        let lowered = format!("{to_builtin}({})", custom.lower(unwrapped));
+
+        // Values are sized before they are written, so the builtin lowered for `allocationSize` is
+        // kept for the `write` of the same value that follows, and the configured `lower` runs once
         quote! {
-            typedef $(type_name) = $(builtin_name);
-            typedef $(ffi_converter_name) = $(builtin_ffi_converter_name);
+            $builtin_helper
+
+            $declaration
+
+            class $ffi_converter_name {
+                static ($type_name, $(&builtin_name))? _sized;
+
+                static $(&builtin_name) _toBuiltin($type_name value) {
+                    return $(&lowered);
+                }
+
+                static $type_name lift($(&ffi_type) value) {
+                    return $lifted;
+                }
+
+                static $(&ffi_type) lower($type_name value) {
+                    return $builtin_ffi_converter_name.lower(_toBuiltin(value));
+                }
+
+                static LiftRetVal<$type_name> read(Uint8List buf) {
+                    final builtin = $builtin_ffi_converter_name.read(buf);
+                    return LiftRetVal($read_value, builtin.bytesRead);
+                }
+
+                static int allocationSize($type_name value) {
+                    final builtin = _toBuiltin(value);
+                    _sized = (value, builtin);
+                    return $builtin_ffi_converter_name.allocationSize(builtin);
+                }
+
+                static int write($type_name value, Uint8List buf) {
+                    final sized = _sized;
+                    _sized = null;
+                    if (sized case (final sizedValue, final builtin) when identical(sizedValue, value)) {
+                        return $builtin_ffi_converter_name.write(builtin, buf);
+                    }
+                    return $builtin_ffi_converter_name.write(_toBuiltin(value), buf);
+                }
+            }
         }
     }
 }
@@ -1,10 +1,11 @@
-use crate::gen::CodeType;
+use crate::gen::{CodeType, Config, DartDefault};
+use anyhow::{bail, Result};
 use genco::prelude::*;
 use heck::ToLowerCamelCase;
-use uniffi_bindgen::interface::{AsType, Enum, Field, Type};
-use uniffi_bindgen::pipeline::general::nodes::Literal;
+use uniffi_bindgen::interface::{AsType, Enum, Field, Literal, Type, Variant};
 
 use super::oracle::{AsCodeType, DartCodeOracle};
+use super::records::{generate_to_string, generate_value_methods, ValueField};
 use super::render::{AsRenderable, Renderable, TypeHelperRenderer};
 
 #[derive(Debug)]
@@ -19,7 +20,7 @@ impl EnumCodeType {
 }
 
 impl CodeType for EnumCodeType {
-    fn type_label(&self) -> String {
+    fn type_label(&self, _config: &Config) -> String {
         DartCodeOracle::class_name(&self.id)
     }
 
@@ -27,15 +28,15 @@ impl CodeType for EnumCodeType {
         DartCodeOracle::class_name(&self.id)
     }
 
-    fn literal(&self, literal: &Literal) -> String {
+    fn literal(&self, literal: &Literal, config: &Config) -> Result<DartDefault> {
         if let Literal::Enum(v, _) = literal {
-            format!(
-                "{}{}",
-                self.type_label(),
+            Ok(DartDefault::Const(format!(
+                "{}.{}",
+                self.type_label(config),
                 DartCodeOracle::enum_variant_name(v)
-            )
+            )))
         } else {
-            unreachable!();
+            bail!("{} has no literal {literal:?}", self.type_label(config))
         }
     }
 
@@ -60,13 +61,108 @@ impl Renderable for EnumCodeType {
 pub fn generate_enum(obj: &Enum, type_helper: &dyn TypeHelperRenderer) -> dart::Tokens {
     let dart_cls_name = &DartCodeOracle::class_name(obj.name());
     let ffi_converter_name = &obj.as_codetype().ffi_converter_name();
-    if obj.is_flat() {
-        quote! {
-            enum $dart_cls_name {
-                $(for variant in obj.variants() =>
-                $(DartCodeOracle::enum_variant_name(variant.name())),)
-                ;
+    // Flat errors still need an exception hierarchy, so only plain flat enums become Dart enums
+    if obj.is_flat() && !type_helper.get_ci().is_name_used_as_error(obj.name()) {
+        // `value` carries the Rust discriminant; the wire format only uses the variant index
+        let discriminants = (0..obj.variants().len()).map(|index| {
+            match obj
+                .variant_discr(index)
+                .expect("flat enum variants have discriminants")
+            {
+                Literal::UInt(value, ..) => value.to_string(),
+                Literal::Int(value, ..) => value.to_string(),
+                discr => unreachable!("Unexpected discriminant {discr:?}"),
             }
+        });
+        // Dart enums can't hold the index of a variant added by a newer library build, so
+        // non-exhaustive enums become classes with a constant per variant plus `unknown`
+        let non_exhaustive = obj.is_non_exhaustive();
+        let declaration = if non_exhaustive {
+            let variant_names = obj
+                .variants()
+                .iter()
+                .map(|variant| DartCodeOracle::enum_variant_name(variant.name()))
+                .collect::<Vec<_>>();
+            quote! {
+                $(DartCodeOracle::doc_comment(obj.docstring()))
+                final class $dart_cls_name {
+                    $(for ((index, variant), discr) in obj.variants().iter().enumerate().zip(discriminants) =>
+                    $(DartCodeOracle::doc_comment(variant.docstring()))
+                    static const $(&variant_names[index]) = $dart_cls_name._($index, $discr, $(format!("\"{}\"", variant_names[index])));
+                    )
+
+                    static const List<$dart_cls_name> values = [$(for name in &variant_names => $name,)];
+
+                    const $dart_cls_name._(this.index, this.value, this.name);
+
+                    $(DartCodeOracle::doc_comment(Some("A variant unknown to these bindings, which can't be passed back to Rust")))
+                    const $dart_cls_name.unknown(this.index) : value = null, name = "unknown";
+
+                    $(DartCodeOracle::doc_comment(Some("Position of the variant in the Rust enum, like [Enum.index]")))
+                    final int index;
+
+                    $(DartCodeOracle::doc_comment(Some("Rust discriminant, or `null` for unknown variants")))
+                    final int? value;
+
+                    final String name;
+
+                    bool get isUnknown => value == null;
+
+                    static $dart_cls_name fromValue(int value) {
+                        return values.firstWhere(
+                            (variant) => variant.value == value,
+                            orElse: () => throw ArgumentError.value(value, "value", $(format!("\"No {dart_cls_name} variant has this value\""))),
+                        );
+                    }
+
+                    @override
+                    bool operator ==(Object other) => other is $dart_cls_name && other.index == index;
+
+                    @override
+                    int get hashCode => index.hashCode;
+
+                    @override
+                    String toString() => $(format!("\"{dart_cls_name}.$name\""));
+                }
+            }
+        } else {
+            quote! {
+                $(DartCodeOracle::doc_comment(obj.docstring()))
+                enum $dart_cls_name {
+                    $(for (variant, discr) in obj.variants().iter().zip(discriminants) =>
+                    $(DartCodeOracle::doc_comment(variant.docstring()))
+                    $(DartCodeOracle::enum_variant_name(variant.name()))($discr),)
+                    ;
+
+                    const $dart_cls_name(this.value);
+
+                    final int value;
+
+                    static $dart_cls_name fromValue(int value) {
+                        return values.firstWhere(
+                            (variant) => variant.value == value,
+                            orElse: () => throw ArgumentError.value(value, "value", $(format!("\"No {dart_cls_name} variant has this value\""))),
+                        );
+                    }
+                }
+            }
+        };
+        let unknown_check = if non_exhaustive {
+            quote! {
+                if (value.isUnknown) {
+                    throw UnsupportedError($(format!("\"Unknown {dart_cls_name} variants cannot be passed to Rust\"")));
+                }
+            }
+        } else {
+            quote!()
+        };
+        let unknown_read = if non_exhaustive {
+            quote!(return LiftRetVal($dart_cls_name.unknown(index - 1), 4);)
+        } else {
+            quote!(throw UniffiInternalError(UniffiInternalError.unexpectedEnumCase, "Unable to determine enum variant");)
+        };
+        quote! {
+            $declaration
 
             class $ffi_converter_name {
                 static LiftRetVal<$dart_cls_name> read( Uint8List buf) {
@@ -80,16 +176,17 @@ pub fn generate_enum(obj: &Enum, type_helper: &dyn TypeHelperRenderer) -> dart::
                             );
                         )
                         default:
-                            throw UniffiInternalError(UniffiInternalError.unexpectedEnumCase, "Unable to determine enum variant");
+                            $unknown_read
                     }
                 }
 
                 static $dart_cls_name lift( RustBuffer buffer) {
-                    return $ffi_converter_name.read(buffer.asUint8List()).value;
+                    return uniffiLiftRustBuffer(buffer, $ffi_converter_name.read);
                 }
 
-                static RustBuffer lower( $dart_cls_name input) {
-                    return toRustBuffer(createUint8ListFromInt(input.index + 1));
+                static RustBuffer lower( $dart_cls_name value) {
+                    $(&unknown_check)
+                    return uniffiLowerIntoRustBuffer(4, (buf) => write(value, buf));
                 }
 
                 static int allocationSize($dart_cls_name _value) {
@@ -97,6 +194,7 @@ pub fn generate_enum(obj: &Enum, type_helper: &dyn TypeHelperRenderer) -> dart::
                 }
 
                 static int write( $dart_cls_name value, Uint8List buf) {
+                    $(&unknown_check)
                     buf.buffer
                         .asByteData(buf.offsetInBytes)
                         .setInt32(0, value.index + 1);
@@ -105,6 +203,16 @@ pub fn generate_enum(obj: &Enum, type_helper: &dyn TypeHelperRenderer) -> dart::
             }
         }
     } else {
+        let config = type_helper.get_config();
+        let variant_cls_name = |variant: &Variant| {
+            config.variant_class_name(dart_cls_name, &DartCodeOracle::class_name(variant.name()))
+        };
+        // Sealed hierarchies let Dart 3 check `switch` statements over the variants for exhaustiveness
+        let (base_modifier, variant_modifier) = if config.sealed_classes() {
+            (quote!(sealed), quote!(final))
+        } else {
+            (quote!(abstract), quote!())
+        };
         let mut variants = vec![];
 
         // helper functions to get the sanitized field name and type strings
@@ -140,15 +248,15 @@ pub fn generate_enum(obj: &Enum, type_helper: &dyn TypeHelperRenderer) -> dart::
             false
         }
 
+        // Rust writes a flat error's `Display` message after its index. Lowering one back only
+        // writes the index, which is all Rust reads.
+        let flat_error = obj.is_flat();
+
         for (index, variant_obj) in obj.variants().iter().enumerate() {
             for f in variant_obj.fields() {
                 type_helper.include_once_check(&f.as_codetype().canonical_name(), &f.as_type());
             }
-            let variant_dart_cls_name = &format!(
-                "{}{}",
-                DartCodeOracle::class_name(variant_obj.name()),
-                dart_cls_name
-            );
+            let variant_dart_cls_name = &variant_cls_name(variant_obj);
 
             // Prepare constructor parameters
             let constructor_params = variant_obj
@@ -166,19 +274,20 @@ pub fn generate_enum(obj: &Enum, type_helper: &dyn TypeHelperRenderer) -> dart::
                 })
                 .collect::<Vec<_>>();
 
-            let constructor_param_list = if variant_obj.fields().len() > 1 {
+            let constructor_param_list = if flat_error {
+                quote!([this.message = ""])
+            } else if variant_obj.fields().len() > 1 {
                 quote!({ $( for p in constructor_params => $p, ) })
             } else {
                 quote!($( for p in constructor_params => $p, ))
             };
 
             // Pre-process field reading code
-            let field_read_code: Vec<dart::Tokens> = variant_obj.fields().iter().enumerate().map(|(i, field)| {
+            let mut field_read_code: Vec<dart::Tokens> = variant_obj.fields().iter().enumerate().map(|(i, field)| {
                 if is_flat_enum(field, type_helper) {
                     // Handle flat enums specially - they serialize as int32 (4 bytes)
                     quote!(
-                        final $(field_name(field, i))_int = buf.buffer.asByteData(new_offset).getInt32(0);
-                        final $(field_name(field, i)) = $(field_ffi_converter_name(field)).lift(toRustBuffer(createUint8ListFromInt($(field_name(field, i))_int)));
+                        final $(field_name(field, i)) = $(field_ffi_converter_name(field)).read(Uint8List.view(buf.buffer, new_offset)).value;
                         new_offset += 4;
                     )
                 } else {
@@ -190,6 +299,14 @@ pub fn generate_enum(obj: &Enum, type_helper: &dyn TypeHelperRenderer) -> dart::
                 }
             }).collect();
 
+            if flat_error {
+                field_read_code.push(quote!(
+                    final message_lifted = FfiConverterString.read(Uint8List.view(buf.buffer, new_offset));
+                    final message = message_lifted.value;
+                    new_offset += message_lifted.bytesRead;
+                ));
+            }
+
             // Pre-process allocation size calculation
             let allocation_parts: Vec<dart::Tokens> = variant_obj.fields().iter().enumerate().map(|(i, field)| {
                 if is_flat_enum(field, type_helper) {
@@ -202,11 +319,9 @@ pub fn generate_enum(obj: &Enum, type_helper: &dyn TypeHelperRenderer) -> dart::
             // Pre-process field write code
             let field_write_code: Vec<dart::Tokens> = variant_obj.fields().iter().enumerate().map(|(i, field)| {
                 if is_flat_enum(field, type_helper) {
-                    // Handle flat enums specially - lower to RustBuffer and extract int32
+                    // Handle flat enums specially - written in place as int32
                     quote!(
-                        final $(field_name(field, i))_buffer = $(field_ffi_converter_name(field)).lower($(field_name(field, i)));
-                        final $(field_name(field, i))_int = $(field_name(field, i))_buffer.asUint8List().buffer.asByteData().getInt32(0);
-                        buf.buffer.asByteData(new_offset).setInt32(0, $(field_name(field, i))_int);
+                        $(field_ffi_converter_name(field)).write($(field_name(field, i)), Uint8List.view(buf.buffer, new_offset));
                         new_offset += 4;
                     )
                 } else {
@@ -216,46 +331,90 @@ pub fn generate_enum(obj: &Enum, type_helper: &dyn TypeHelperRenderer) -> dart::
                 }
             }).collect();
 
-            // Generate simple toString() method for error enum variants
-            let to_string_method: dart::Tokens =
-                if type_helper.get_ci().is_name_used_as_error(obj.name()) {
-                    if variant_obj.has_fields() {
-                        let field_interpolations = variant_obj
-                            .fields()
-                            .iter()
-                            .enumerate()
-                            .map(|(i, field)| format!("${}", field_name(field, i)))
-                            .collect::<Vec<_>>()
-                            .join(", ");
-                        let to_string_with_fields =
-                            format!("\"{variant_dart_cls_name}({field_interpolations})\"");
-                        quote!(
-                            @override
-                            String toString() {
-                                return $(&to_string_with_fields);
-                            }
-                        )
-                    } else {
-                        quote!(
-                            @override
-                            String toString() {
-                                return $(format!("\"{}\"", variant_dart_cls_name));
-                            }
-                        )
+            let mut value_fields = variant_obj
+                .fields()
+                .iter()
+                .enumerate()
+                .map(|(i, field)| {
+                    ValueField::new(
+                        field_name(field, i),
+                        quote!($(field_type(field, type_helper))),
+                        &field.as_type(),
+                        config,
+                    )
+                })
+                .collect::<Vec<_>>();
+            if flat_error {
+                value_fields.push(ValueField::new(
+                    "message".to_string(),
+                    quote!(String),
+                    &Type::String,
+                    config,
+                ));
+            }
+            let value_methods = generate_value_methods(
+                variant_dart_cls_name,
+                quote!($variant_dart_cls_name._),
+                &value_fields,
+                Some(index + 1),
+                false,
+            );
+
+            // Error variants keep their terse positional toString(); other variants list fields by name
+            let to_string_method: dart::Tokens = if flat_error {
+                let to_string_with_message = format!("\"{variant_dart_cls_name}($message)\"");
+                quote!(
+                    @override
+                    String toString() {
+                        return message.isEmpty ? $(format!("\"{variant_dart_cls_name}\"")) : $(&to_string_with_message);
                     }
+                )
+            } else if type_helper.get_ci().is_name_used_as_error(obj.name()) {
+                if variant_obj.has_fields() {
+                    let field_interpolations = variant_obj
+                        .fields()
+                        .iter()
+                        .enumerate()
+                        .map(|(i, field)| format!("${}", field_name(field, i)))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let to_string_with_fields =
+                        format!("\"{variant_dart_cls_name}({field_interpolations})\"");
+                    quote!(
+                        @override
+                        String toString() {
+                            return $(&to_string_with_fields);
+                        }
+                    )
                 } else {
-                    quote!()
-                };
+                    quote!(
+                        @override
+                        String toString() {
+                            return $(format!("\"{}\"", variant_dart_cls_name));
+                        }
+                    )
+                }
+            } else {
+                generate_to_string(variant_dart_cls_name, &value_fields)
+            };
 
             variants.push(quote!{
-                class $variant_dart_cls_name extends $dart_cls_name {
-                    $(for (i, field) in variant_obj.fields().iter().enumerate() => final $(field_type(field, type_helper)) $(field_name(field, i));  )
+                $(DartCodeOracle::doc_comment(variant_obj.docstring()))
+                $(&variant_modifier) class $variant_dart_cls_name extends $dart_cls_name {
+                    $(for (i, field) in variant_obj.fields().iter().enumerate() =>
+                        $(DartCodeOracle::doc_comment(field.docstring()))
+                        final $(field_type(field, type_helper)) $(field_name(field, i));
+                    )
+                    $(if flat_error {
+                        $(DartCodeOracle::doc_comment(Some("The error's message, from its `Display` implementation in Rust")))
+                        final String message;
+                    })
 
                     // Add the public const constructor
                     $variant_dart_cls_name($constructor_param_list);
 
                     // Keep the private constructor used by `read`
-                    $variant_dart_cls_name._($(for (i, field) in variant_obj.fields().iter().enumerate() => $(field_type(field, type_helper)) this.$(field_name(field, i)), ));
+                    $variant_dart_cls_name._($(for (i, field) in variant_obj.fields().iter().enumerate() => $(field_type(field, type_helper)) this.$(field_name(field, i)), )$(if flat_error { this.message }));
 
                     static LiftRetVal<$variant_dart_cls_name> read( Uint8List buf) {
                         int new_offset = buf.offsetInBytes;
@@ -263,14 +422,13 @@ pub fn generate_enum(obj: &Enum, type_helper: &dyn TypeHelperRenderer) -> dart::
                         $(for code in &field_read_code => $code)
                         return LiftRetVal($variant_dart_cls_name._(
                             $(for (i, field) in variant_obj.fields().iter().enumerate() => $(field_name(field, i)),)
-                        ), new_offset);
+                            $(if flat_error { message })
+                        ), new_offset - buf.offsetInBytes);
                     }
 
                     @override
                     RustBuffer lower() {
-                        final buf = Uint8List(allocationSize());
-                        write(buf);
-                        return toRustBuffer(buf);
+                        return uniffiLowerIntoRustBuffer(allocationSize(), write);
                     }
 
                     @override
@@ -285,14 +443,87 @@ pub fn generate_enum(obj: &Enum, type_helper: &dyn TypeHelperRenderer) -> dart::
 
                         $(for code in &field_write_code => $code)
 
-                        return new_offset;
+                        return new_offset - buf.offsetInBytes;
                     }
 
+                    $value_methods
+
                     $to_string_method
                 }
             });
         }
 
+        // Non-exhaustive enums lift variants added by newer library builds into an extra class
+        // that keeps the variant's index instead of failing
+        let non_exhaustive = obj.is_non_exhaustive();
+        let unknown_cls_name = &config.variant_class_name(dart_cls_name, "Unknown");
+        if non_exhaustive {
+            let value_fields = [ValueField::new(
+                "index".to_string(),
+                quote!(int),
+                &Type::Int32,
+                config,
+            )];
+            let value_methods = generate_value_methods(
+                unknown_cls_name,
+                quote!($unknown_cls_name),
+                &value_fields,
+                Some(0),
+                false,
+            );
+            let to_string_method = generate_to_string(unknown_cls_name, &value_fields);
+            let unsupported = format!("\"{unknown_cls_name} cannot be passed to Rust\"");
+            variants.push(quote! {
+                $(&variant_modifier) class $unknown_cls_name extends $dart_cls_name {
+                    $(DartCodeOracle::doc_comment(Some("Position of the variant in the Rust enum")))
+                    final int index;
+
+                    $unknown_cls_name(this.index);
+
+                    @override
+                    RustBuffer lower() {
+                        throw UnsupportedError($(&unsupported));
+                    }
+
+                    @override
+                    int allocationSize() {
+                        throw UnsupportedError($(&unsupported));
+                    }
+
+                    @override
+                    int write( Uint8List buf) {
+                        throw UnsupportedError($(&unsupported));
+                    }
+
+                    $value_methods
+
+                    $to_string_method
+                }
+            });
+        }
+        // The payload layout of an unknown variant isn't known, so it can only be skipped by
+        // `lift`, where it runs to the end of the buffer. Inside other values it would misalign
+        // the fields that follow.
+        let unknown_lift = if non_exhaustive {
+            quote! {
+                final buf = buffer.asUint8List();
+                final index = buf.buffer.asByteData(buf.offsetInBytes).getInt32(0);
+                if (index < 1 || index > $(obj.variants().len())) {
+                    buffer.free();
+                    return $unknown_cls_name(index - 1);
+                }
+            }
+        } else {
+            quote!()
+        };
+        let unknown_read = if non_exhaustive {
+            let message =
+                format!("\"Unknown {dart_cls_name} variants can only be lifted as a whole value\"");
+            quote!(throw UniffiInternalError(UniffiInternalError.unexpectedEnumCase, $message);)
+        } else {
+            quote!(throw UniffiInternalError(UniffiInternalError.unexpectedEnumCase, "Unable to determine enum variant");)
+        };
+
         let is_error_enum = type_helper.get_ci().is_name_used_as_error(obj.name());
         let implements_exception = if is_error_enum {
             quote!( implements Exception)
@@ -319,7 +550,8 @@ pub fn generate_enum(obj: &Enum, type_helper: &dyn TypeHelperRenderer) -> dart::
         };
 
         quote! {
-            abstract class $dart_cls_name $implements_exception {
+            $(DartCodeOracle::doc_comment(obj.docstring()))
+            $base_modifier class $dart_cls_name $implements_exception {
                 RustBuffer lower();
                 int allocationSize();
                 int write( Uint8List buf);
@@ -327,18 +559,21 @@ pub fn generate_enum(obj: &Enum, type_helper: &dyn TypeHelperRenderer) -> dart::
 
             class $ffi_converter_name {
                 static $dart_cls_name lift( RustBuffer buffer) {
-                    return $ffi_converter_name.read(buffer.asUint8List()).value;
+                    $unknown_lift
+                    return uniffiLiftRustBuffer(buffer, $ffi_converter_name.read);
                 }
 
                 static LiftRetVal<$dart_cls_name> read( Uint8List buf) {
                     final index = buf.buffer.asByteData(buf.offsetInBytes).getInt32(0);
+                    // The variant reads its fields after the index, which is counted here
                     final subview = Uint8List.view(buf.buffer, buf.offsetInBytes + 4);
                     switch(index) {
                         $(for (index, variant) in obj.variants().iter().enumerate() =>
                         case $(index + 1):
-                            return $(format!("{}{}", DartCodeOracle::class_name(variant.name()), dart_cls_name)).read(subview);
+                            return $(variant_cls_name(variant)).read(subview).copyWithOffset(4);
                         )
-                        default:  throw UniffiInternalError(UniffiInternalError.unexpectedEnumCase, "Unable to determine enum variant");
+                        default:
+                            $unknown_read
                     }
                 }
 
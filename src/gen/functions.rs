@@ -1,15 +1,96 @@
 use genco::prelude::*;
 use heck::ToLowerCamelCase;
-use uniffi_bindgen::interface::{AsType, Function};
+use uniffi_bindgen::interface::{Argument, AsType, Function};
 
 use crate::gen::oracle::DartCodeOracle;
 use crate::gen::render::AsRenderable;
 
 use super::oracle::AsCodeType;
+use super::records::is_nullable;
 use super::render::TypeHelperRenderer;
+use super::DartDefault;
+
+/// Render the Dart parameter list of a callable.
+///
+/// Arguments without a default stay required positional parameters, in declaration order.
+/// Arguments with a default become optional named parameters initialized with the default, or
+/// nullable ones standing for it if the default isn't constant.
+pub fn generate_parameters(
+    arguments: &[&Argument],
+    type_helper: &dyn TypeHelperRenderer,
+) -> dart::Tokens {
+    render_parameters(arguments, vec![], type_helper)
+}
+
+/// Render the Dart parameter list of an async callable, which additionally takes the optional
+/// named `cancellationToken` and `timeout` parameters that abort the Rust future.
+pub fn generate_async_parameters(
+    arguments: &[&Argument],
+    type_helper: &dyn TypeHelperRenderer,
+) -> dart::Tokens {
+    render_parameters(
+        arguments,
+        vec![
+            quote!(UniffiCancellationToken? cancellationToken),
+            quote!(Duration? timeout),
+        ],
+        type_helper,
+    )
+}
+
+fn render_parameters(
+    arguments: &[&Argument],
+    extra_named: Vec<dart::Tokens>,
+    type_helper: &dyn TypeHelperRenderer,
+) -> dart::Tokens {
+    let render_param = |arg: &Argument| {
+        let ty = arg.as_renderable().render_type(&arg.as_type(), type_helper);
+        quote!($ty $(DartCodeOracle::var_name(arg.name())))
+    };
+    let (named, positional): (Vec<&Argument>, Vec<&Argument>) = arguments
+        .iter()
+        .copied()
+        .partition(|arg| arg.default_value().is_some());
+
+    let named_params = named
+        .into_iter()
+        .map(|arg| {
+            let config = type_helper.get_config();
+            match DartCodeOracle::argument_default(arg, config)
+                .expect("partitioned on default_value")
+            {
+                DartDefault::Const(default) => quote!($(render_param(arg)) = $default),
+                // Replaced when the argument is lowered
+                DartDefault::Runtime(_) => {
+                    let ty = arg.as_renderable().render_type(&arg.as_type(), type_helper);
+                    let ty = if is_nullable(&arg.as_type(), config) {
+                        ty
+                    } else {
+                        quote!($ty?)
+                    };
+                    quote!($ty $(DartCodeOracle::var_name(arg.name())))
+                }
+            }
+        })
+        .chain(extra_named)
+        .collect::<Vec<_>>();
+
+    let named_params = if named_params.is_empty() {
+        quote!()
+    } else {
+        quote!({$(for p in named_params => $p,)})
+    };
+
+    quote!($(for arg in positional => $(render_param(arg)),) $named_params)
+}
 
 pub fn generate_function(func: &Function, type_helper: &dyn TypeHelperRenderer) -> dart::Tokens {
-    let args = quote!($(for arg in &func.arguments() => $(&arg.as_renderable().render_type(&arg.as_type(), type_helper)) $(DartCodeOracle::var_name(arg.name())),));
+    let args = if func.is_async() {
+        generate_async_parameters(&func.arguments(), type_helper)
+    } else {
+        generate_parameters(&func.arguments(), type_helper)
+    };
+    let doc = DartCodeOracle::doc_comment(func.docstring());
 
     let (ret, lifter) = if let Some(ret) = func.return_type() {
         (
@@ -30,37 +111,44 @@ pub fn generate_function(func: &Function, type_helper: &dyn TypeHelperRenderer)
         quote!(null)
     };
 
-    // Use centralized callback-aware argument lowering
+    // Use centralized callback-aware argument lowering. The call status is named apart from the
+    // arguments, which stay in scope of the closure that lowers them
     if func.is_async() {
         quote!(
+            $doc
             Future<$ret> $(DartCodeOracle::fn_name(func.name()))($args) {
                 return uniffiRustCallAsync(
                   () => $(DartCodeOracle::find_lib_instance()).$(func.ffi_func().name())(
-                    $(for arg in &func.arguments() => $(DartCodeOracle::lower_arg_with_callback_handling(arg)),)
+                    $(for arg in &func.arguments() => $(DartCodeOracle::lower_arg_with_callback_handling(arg, type_helper.get_config())),)
                   ),
                   $(DartCodeOracle::async_poll(func, type_helper.get_ci())),
+                  $(DartCodeOracle::async_cancel(func, type_helper.get_ci())),
                   $(DartCodeOracle::async_complete(func, type_helper.get_ci())),
                   $(DartCodeOracle::async_free(func, type_helper.get_ci())),
-                  $lifter,
+                  $(DartCodeOracle::async_lift(func.return_type())),
                   $error_handler,
+                  cancellationToken,
+                  timeout,
                 );
             }
         )
     } else if ret == quote!(void) {
         quote!(
+            $(&doc)
             $ret $(DartCodeOracle::fn_name(func.name()))($args) {
-                return rustCall((status) {
+                return rustCall((uniffiStatus) {
                     $(DartCodeOracle::find_lib_instance()).$(func.ffi_func().name())(
-                        $(for arg in &func.arguments() => $(DartCodeOracle::lower_arg_with_callback_handling(arg)),) status
+                        $(for arg in &func.arguments() => $(DartCodeOracle::lower_arg_with_callback_handling(arg, type_helper.get_config())),) uniffiStatus
                     );
                 }, $error_handler);
             }
         )
     } else {
         quote!(
+            $(&doc)
             $ret $(DartCodeOracle::fn_name(func.name()))($args) {
-                return rustCall((status) => $lifter($(DartCodeOracle::find_lib_instance()).$(func.ffi_func().name())(
-                    $(for arg in &func.arguments() => $(DartCodeOracle::lower_arg_with_callback_handling(arg)),) status
+                return rustCall((uniffiStatus) => $lifter($(DartCodeOracle::find_lib_instance()).$(func.ffi_func().name())(
+                    $(for arg in &func.arguments() => $(DartCodeOracle::lower_arg_with_callback_handling(arg, type_helper.get_config())),) uniffiStatus
                 )), $error_handler);
             }
         )
@@ -1,10 +1,11 @@
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::io::Read;
 use std::process::Command;
 
-use anyhow::Result;
-use camino::Utf8Path;
+use anyhow::{bail, Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
 
 use genco::fmt;
 use genco::prelude::*;
@@ -31,7 +32,7 @@ mod render;
 pub mod stream;
 mod types;
 
-pub use code_type::CodeType;
+pub use code_type::{CodeType, DartDefault};
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -39,6 +40,79 @@ pub struct Config {
     cdylib_name: Option<String>,
     #[serde(default)]
     external_packages: HashMap<String, String>,
+    /// Render enums with associated data as `sealed` class hierarchies. Defaults to `true`; set to
+    /// `false` for `abstract` classes that can be extended outside the bindings.
+    sealed_classes: Option<bool>,
+    /// Name of the class generated for each enum variant, with `{variant}` and `{enum}`
+    /// placeholders for the Dart names of both. Defaults to `{variant}{enum}`.
+    variant_class_name: Option<String>,
+    /// Expose `u64` values as `BigInt`, covering the full range. Defaults to `false`, where
+    /// values above `i64::MAX` come back as negative `int`s.
+    uint64_as_bigint: Option<bool>,
+    /// Render `Option<Option<T>>` as `Optional<T?>`, so `Some(None)` stays distinct from `None`.
+    /// Defaults to `false`, where both are `null`.
+    nested_optionals: Option<bool>,
+    /// Expose sequences of numbers as `dart:typed_data` lists such as `Int32List`, copied in bulk
+    /// instead of element by element. Defaults to `false`, where they are `List<int>` or
+    /// `List<double>`.
+    typed_data_sequences: Option<bool>,
+    /// Lift bytes as views into the `RustBuffer` they arrived in, freed once the `Uint8List` is
+    /// garbage collected, and lower them straight into a Rust allocation. Lifting needs the
+    /// scaffolding from `uniffi_dart::generate_scaffolding` and copies without it. Defaults to
+    /// `false`, where bytes are copied.
+    zero_copy_bytes: Option<bool>,
+    /// Dart representations of custom types, keyed by the custom type's name
+    #[serde(default)]
+    custom_types: HashMap<String, CustomTypeConfig>,
+}
+
+/// How a custom type is converted to and from its builtin type on the Dart side.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CustomTypeConfig {
+    /// The Dart type the custom type is exposed as, e.g. `Uri`. Defaults to the builtin's type.
+    type_name: Option<String>,
+    /// Libraries the conversion expressions need, e.g. `package:uuid/uuid.dart`
+    #[serde(default)]
+    imports: Vec<String>,
+    /// Expression converting the builtin value `{}` into the custom type
+    lift: Option<String>,
+    /// Expression converting the custom value `{}` into the builtin type
+    lower: Option<String>,
+    /// Declare the custom type as an `extension type` wrapping the Dart type, so it is distinct
+    /// from the builtin and from other custom types at compile time
+    #[serde(default)]
+    extension_type: bool,
+    /// Expose a custom type over `u64` as `BigInt` (or as `int`), regardless of
+    /// `uint64_as_bigint`
+    bigint: Option<bool>,
+}
+
+impl CustomTypeConfig {
+    pub fn type_name(&self) -> Option<&str> {
+        self.type_name.as_deref()
+    }
+
+    pub fn lift(&self, value: &str) -> String {
+        match &self.lift {
+            Some(lift) => lift.replace("{}", value),
+            None => value.to_string(),
+        }
+    }
+
+    pub fn lower(&self, value: &str) -> String {
+        match &self.lower {
+            Some(lower) => lower.replace("{}", value),
+            None => value.to_string(),
+        }
+    }
+
+    pub fn extension_type(&self) -> bool {
+        self.extension_type
+    }
+
+    pub fn bigint(&self) -> Option<bool> {
+        self.bigint
+    }
 }
 
 impl From<&ComponentInterface> for Config {
@@ -47,6 +121,13 @@ impl From<&ComponentInterface> for Config {
             package_name: Some(ci.namespace().to_owned()),
             cdylib_name: Some(ci.namespace().to_owned()),
             external_packages: HashMap::new(),
+            sealed_classes: None,
+            variant_class_name: None,
+            uint64_as_bigint: None,
+            nested_optionals: None,
+            typed_data_sequences: None,
+            zero_copy_bytes: None,
+            custom_types: HashMap::new(),
         }
     }
 }
@@ -67,6 +148,47 @@ impl Config {
             "uniffi".into()
         }
     }
+
+    pub fn sealed_classes(&self) -> bool {
+        self.sealed_classes.unwrap_or(true)
+    }
+
+    /// The Dart class name of `variant`, given the Dart names of the variant and its enum.
+    pub fn variant_class_name(&self, enum_name: &str, variant_name: &str) -> String {
+        self.variant_class_name
+            .as_deref()
+            .unwrap_or("{variant}{enum}")
+            .replace("{variant}", variant_name)
+            .replace("{enum}", enum_name)
+    }
+
+    pub fn uint64_as_bigint(&self) -> bool {
+        self.uint64_as_bigint.unwrap_or(false)
+    }
+
+    pub fn nested_optionals(&self) -> bool {
+        self.nested_optionals.unwrap_or(false)
+    }
+
+    pub fn typed_data_sequences(&self) -> bool {
+        self.typed_data_sequences.unwrap_or(false)
+    }
+
+    pub fn zero_copy_bytes(&self) -> bool {
+        self.zero_copy_bytes.unwrap_or(false)
+    }
+
+    pub fn custom_type(&self, name: &str) -> Option<&CustomTypeConfig> {
+        self.custom_types.get(name)
+    }
+
+    /// The libraries imported by the configured custom type conversions, without duplicates.
+    pub fn custom_type_imports(&self) -> BTreeSet<&str> {
+        self.custom_types
+            .values()
+            .flat_map(|custom| custom.imports.iter().map(String::as_str))
+            .collect()
+    }
 }
 
 pub struct DartWrapper<'a> {
@@ -77,7 +199,7 @@ pub struct DartWrapper<'a> {
 
 impl<'a> DartWrapper<'a> {
     pub fn new(ci: &'a ComponentInterface, config: &'a Config) -> Self {
-        let type_renderer = TypeHelpersRenderer::new(ci);
+        let type_renderer = TypeHelpersRenderer::new(config, ci);
         DartWrapper {
             ci,
             config,
@@ -213,6 +335,9 @@ impl BindingGenerator for DartBindingGenerator {
         components: &[uniffi_bindgen::Component<Self::Config>],
     ) -> Result<()> {
         for Component { ci, config, .. } in components {
+            DartCodeOracle::check_default_values(ci, config)?;
+            DartCodeOracle::check_non_exhaustive_enums(ci)?;
+            DartCodeOracle::check_async_callback_errors(ci)?;
             let filename = settings.out_dir.join(format!("{}.dart", ci.namespace()));
             let tokens = DartWrapper::new(ci, config).generate();
             let file = std::fs::File::create(filename)?;
@@ -228,6 +353,10 @@ impl BindingGenerator for DartBindingGenerator {
             tokens.format_file(&mut w.as_formatter(&fmt), &config)?;
         }
 
+        if !settings.try_format_code {
+            return Ok(());
+        }
+
         // Run full Dart formatter on the output directory as a best-effort step.
         // This is non-fatal: failures will only emit a warning.
         let mut format_command = Command::new("dart");
@@ -272,9 +401,78 @@ impl BindingGenerator for DartBindingGenerator {
     }
 }
 
+/// Supplies the UDL file and `uniffi.toml` of a single crate, for when `cargo metadata`
+/// isn't available to locate them.
 pub struct LocalConfigSupplier(String);
+
+impl LocalConfigSupplier {
+    // The UDL file sits in the crate's `src` directory, and `uniffi.toml` in its root
+    fn crate_root(&self) -> Option<&Utf8Path> {
+        Utf8Path::new(&self.0).parent()?.parent()
+    }
+
+    /// Whether `crate_name` is the library crate the UDL file belongs to.
+    fn owns(&self, crate_name: &str) -> Result<bool> {
+        #[derive(Deserialize)]
+        struct Manifest {
+            package: Package,
+            #[serde(default)]
+            lib: Lib,
+        }
+        #[derive(Deserialize)]
+        struct Package {
+            name: String,
+        }
+        #[derive(Default, Deserialize)]
+        struct Lib {
+            name: Option<String>,
+        }
+
+        let path = self
+            .crate_root()
+            .with_context(|| format!("{} isn't inside a crate", self.0))?
+            .join("Cargo.toml");
+        let contents =
+            std::fs::read_to_string(&path).with_context(|| format!("failed to read {path}"))?;
+        let manifest: Manifest =
+            toml::from_str(&contents).with_context(|| format!("failed to parse {path}"))?;
+        let name = manifest
+            .lib
+            .name
+            .unwrap_or_else(|| manifest.package.name.replace('-', "_"));
+        Ok(name == crate_name)
+    }
+}
+
 impl BindgenCrateConfigSupplier for LocalConfigSupplier {
-    fn get_udl(&self, _crate_name: &str, _udl_name: &str) -> Result<String> {
+    fn get_toml(&self, crate_name: &str) -> Result<Option<toml::value::Table>> {
+        match self.get_toml_path(crate_name) {
+            Some(path) => {
+                let contents = std::fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read {path}"))?;
+                let table =
+                    toml::from_str(&contents).with_context(|| format!("failed to parse {path}"))?;
+                Ok(Some(table))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn get_toml_path(&self, crate_name: &str) -> Option<Utf8PathBuf> {
+        // Other crates in the library keep their default config
+        if !self.owns(crate_name).unwrap_or(false) {
+            return None;
+        }
+        Some(self.crate_root()?.join("uniffi.toml")).filter(|path| path.exists())
+    }
+
+    fn get_udl(&self, crate_name: &str, udl_name: &str) -> Result<String> {
+        if !self.owns(crate_name)? {
+            bail!(
+                "Can't find `{udl_name}.udl` of crate `{crate_name}`: only {} is known without the `cargo-metadata` feature",
+                self.0
+            );
+        }
         let file = std::fs::File::open(self.0.clone())?;
         let mut reader = std::io::BufReader::new(file);
         let mut content = String::new();
@@ -283,6 +481,11 @@ impl BindgenCrateConfigSupplier for LocalConfigSupplier {
     }
 }
 
+/// Generate Dart bindings for a single UDL file.
+///
+/// With `library_mode` set, all components are read from `library_file` instead, see
+/// [`generate_dart_bindings_from_library`]. Without the `cargo-metadata` feature, `udl_file`
+/// and the `uniffi.toml` of its crate then configure every component.
 pub fn generate_dart_bindings(
     udl_file: &Utf8Path,
     config_file_override: Option<&Utf8Path>,
@@ -291,19 +494,21 @@ pub fn generate_dart_bindings(
     library_mode: bool,
 ) -> anyhow::Result<()> {
     if library_mode {
+        let out_dir =
+            out_dir_override.context("an output directory is required in library mode")?;
+        let config_supplier = crate_config_supplier(udl_file)?;
         uniffi_bindgen::library_mode::generate_bindings(
             library_file,
             None,
             &DartBindingGenerator {},
-            &LocalConfigSupplier(udl_file.to_string()),
-            None,
-            out_dir_override.unwrap(),
+            &*config_supplier,
+            config_file_override,
+            out_dir,
             true,
         )?;
         Ok(())
     } else {
-        uniffi_bindgen::generate_external_bindings(
-            &DartBindingGenerator {},
+        generate_dart_bindings_from_udl(
             udl_file,
             config_file_override,
             out_dir_override,
@@ -313,3 +518,70 @@ pub fn generate_dart_bindings(
         )
     }
 }
+
+/// Generate Dart bindings for the component a UDL file describes.
+///
+/// `library_file`, when given, supplies metadata from proc-macros alongside the UDL, and
+/// `crate_name` overrides the crate the UDL is assumed to belong to.
+pub fn generate_dart_bindings_from_udl(
+    udl_file: &Utf8Path,
+    config_file_override: Option<&Utf8Path>,
+    out_dir_override: Option<&Utf8Path>,
+    library_file: Option<&Utf8Path>,
+    crate_name: Option<&str>,
+    try_format_code: bool,
+) -> anyhow::Result<()> {
+    uniffi_bindgen::generate_external_bindings(
+        &DartBindingGenerator {},
+        udl_file,
+        config_file_override,
+        out_dir_override,
+        library_file,
+        crate_name,
+        try_format_code,
+    )
+}
+
+/// Generate Dart bindings for every UniFFI component embedded in a compiled library.
+///
+/// No UDL file is needed: each crate's `uniffi.toml` (and UDL, for crates that have one)
+/// is located through `cargo metadata`. One `.dart` file is written per component, and
+/// `crate_name` limits generation to a single crate.
+#[cfg(feature = "cargo-metadata")]
+pub fn generate_dart_bindings_from_library(
+    library_file: &Utf8Path,
+    crate_name: Option<String>,
+    config_file_override: Option<&Utf8Path>,
+    out_dir: &Utf8Path,
+    try_format_code: bool,
+) -> anyhow::Result<Vec<Component<Config>>> {
+    uniffi_bindgen::library_mode::generate_bindings(
+        library_file,
+        crate_name,
+        &DartBindingGenerator {},
+        &cargo_metadata_config_supplier()?,
+        config_file_override,
+        out_dir,
+        try_format_code,
+    )
+}
+
+#[cfg(feature = "cargo-metadata")]
+fn cargo_metadata_config_supplier() -> Result<uniffi_bindgen::cargo_metadata::CrateConfigSupplier> {
+    let metadata = cargo_metadata::MetadataCommand::new()
+        .exec()
+        .context("error running cargo metadata")?;
+    Ok(uniffi_bindgen::cargo_metadata::CrateConfigSupplier::from(
+        metadata,
+    ))
+}
+
+#[cfg(feature = "cargo-metadata")]
+fn crate_config_supplier(_udl_file: &Utf8Path) -> Result<Box<dyn BindgenCrateConfigSupplier>> {
+    Ok(Box::new(cargo_metadata_config_supplier()?))
+}
+
+#[cfg(not(feature = "cargo-metadata"))]
+fn crate_config_supplier(udl_file: &Utf8Path) -> Result<Box<dyn BindgenCrateConfigSupplier>> {
+    Ok(Box::new(LocalConfigSupplier(udl_file.to_string())))
+}
@@ -5,16 +5,16 @@ use crate::gen::callback_interface::{
     generate_callback_functions, generate_callback_interface,
     generate_callback_interface_vtable_init_function, generate_callback_vtable_interface,
 };
-use crate::gen::CodeType;
+use crate::gen::{CodeType, Config};
 use heck::ToLowerCamelCase;
 use std::string::ToString;
 use uniffi_bindgen::interface::{AsType, Method, Object, ObjectImpl, UniffiTrait};
-use uniffi_bindgen::pipeline::general::nodes::Literal;
 
 use crate::gen::oracle::{AsCodeType, DartCodeOracle};
 use crate::gen::render::AsRenderable;
 use crate::gen::render::{Renderable, TypeHelperRenderer};
 
+use super::functions::{generate_async_parameters, generate_parameters};
 use super::stream::generate_stream;
 
 #[derive(Debug)]
@@ -30,7 +30,7 @@ impl ObjectCodeType {
 }
 
 impl CodeType for ObjectCodeType {
-    fn type_label(&self) -> String {
+    fn type_label(&self, _config: &Config) -> String {
         DartCodeOracle::class_name(&self.id)
     }
 
@@ -38,10 +38,6 @@ impl CodeType for ObjectCodeType {
         self.id.to_string()
     }
 
-    fn literal(&self, _literal: &Literal) -> String {
-        unreachable!();
-    }
-
     fn ffi_converter_name(&self) -> String {
         match self.imp {
             ObjectImpl::Struct => self.canonical_name().to_string(), // Objects will use factory methods
@@ -68,6 +64,7 @@ pub fn generate_object(obj: &Object, type_helper: &dyn TypeHelperRenderer) -> da
     if obj.has_callback_interface() {
         let interface = generate_callback_interface(
             obj.name(),
+            obj.docstring(),
             &obj.as_codetype().ffi_converter_name(),
             &obj.methods(),
             type_helper,
@@ -126,7 +123,8 @@ pub fn generate_object(obj: &Object, type_helper: &dyn TypeHelperRenderer) -> da
 
         // Check if function can throw errors
         let error_handler = if let Some(error_type) = constructor.throws_type() {
-            let error_name = DartCodeOracle::class_name(error_type.name().unwrap_or("UnknownError"));
+            let error_name =
+                DartCodeOracle::class_name(error_type.name().unwrap_or("UnknownError"));
             // Use the consistent Exception naming for error handlers
             let handler_name = format!("{}ErrorHandler", error_name.to_lower_camel_case());
             quote!($(handler_name))
@@ -134,12 +132,8 @@ pub fn generate_object(obj: &Object, type_helper: &dyn TypeHelperRenderer) -> da
             quote!(null)
         };
 
-        let dart_params = quote!($(for arg in constructor.arguments() =>
-            $(DartCodeOracle::dart_type_label(Some(&arg.as_type()))) $(DartCodeOracle::var_name(arg.name())),
-        ));
-
         let ffi_call_args = quote!($(for arg in constructor.arguments() =>
-            $(DartCodeOracle::type_lower_fn(&arg.as_type(), quote!($(DartCodeOracle::var_name(arg.name()))))),)
+            $(DartCodeOracle::lower_arg_with_callback_handling(arg, type_helper.get_config())),)
         );
 
         // Ensure argument types are included
@@ -147,11 +141,45 @@ pub fn generate_object(obj: &Object, type_helper: &dyn TypeHelperRenderer) -> da
             type_helper.include_once_check(&arg.as_codetype().canonical_name(), &arg.as_type());
         }
 
+        // Dart constructors can't be async, so async ones become static factories, with `new`
+        // turning into `create`
+        if constructor.is_async() {
+            let factory_name = if constructor_name == "new" {
+                "create".to_string()
+            } else {
+                DartCodeOracle::fn_name(constructor_name)
+            };
+            let dart_params = generate_async_parameters(&constructor.arguments(), type_helper);
+            let ci = type_helper.get_ci();
+
+            return quote! {
+                $(DartCodeOracle::doc_comment(constructor.docstring()))
+                static Future<$cls_name> $factory_name($dart_params) {
+                    return uniffiRustCallAsync(
+                        () => $lib_instance.$ffi_func_name(
+                            $ffi_call_args
+                        ),
+                        $(DartCodeOracle::async_poll(constructor, ci)),
+                        $(DartCodeOracle::async_cancel(constructor, ci)),
+                        $(DartCodeOracle::async_complete(constructor, ci)),
+                        $(DartCodeOracle::async_free(constructor, ci)),
+                        (handle) => $cls_name._(Pointer<Void>.fromAddress(handle)),
+                        $error_handler,
+                        cancellationToken,
+                        timeout,
+                    );
+                }
+            };
+        }
+
+        let dart_params = generate_parameters(&constructor.arguments(), type_helper);
+
         quote! {
             // Public constructor
-            $dart_constructor_decl($dart_params) : _ptr = rustCall((status) =>
+            $(DartCodeOracle::doc_comment(constructor.docstring()))
+            $dart_constructor_decl($dart_params) : _ptr = rustCall((uniffiStatus) =>
                 $lib_instance.$ffi_func_name(
-                    $ffi_call_args status
+                    $ffi_call_args uniffiStatus
                 ),
                 $error_handler
             ) {
@@ -171,7 +199,7 @@ pub fn generate_object(obj: &Object, type_helper: &dyn TypeHelperRenderer) -> da
             class $(&error_handler_name) extends UniffiRustCallStatusErrorHandler {
                 @override
                 Exception lift(RustBuffer errorBuf) {
-                    return $(cls_name).read(errorBuf.asUint8List()).value;
+                    return uniffiLiftRustBuffer(errorBuf, $(cls_name).read);
                 }
             }
 
@@ -237,6 +265,7 @@ pub fn generate_object(obj: &Object, type_helper: &dyn TypeHelperRenderer) -> da
           rustCall((status) => $lib_instance.$ffi_object_free_name(ptr, status));
         });
 
+        $(DartCodeOracle::doc_comment(obj.docstring()))
         class $cls_name $implements_clause {
             late final Pointer<Void> _ptr;
 
@@ -298,7 +327,12 @@ pub fn generate_object(obj: &Object, type_helper: &dyn TypeHelperRenderer) -> da
 #[allow(unused_variables)]
 pub fn generate_method(func: &Method, type_helper: &dyn TypeHelperRenderer) -> dart::Tokens {
     // if func.takes_self_by_arc() {} // TODO: Do something about this condition
-    let args = quote!($(for arg in &func.arguments() => $(&arg.as_renderable().render_type(&arg.as_type(), type_helper)) $(DartCodeOracle::var_name(arg.name())),));
+    let args = if func.is_async() {
+        generate_async_parameters(&func.arguments(), type_helper)
+    } else {
+        generate_parameters(&func.arguments(), type_helper)
+    };
+    let doc = DartCodeOracle::doc_comment(func.docstring());
 
     let (ret, lifter) = if let Some(ret) = func.return_type() {
         (
@@ -320,51 +354,45 @@ pub fn generate_method(func: &Method, type_helper: &dyn TypeHelperRenderer) -> d
     };
 
     if func.is_async() {
-        // For async methods returning objects, we need to convert the int pointer to Pointer<Void>
-        let async_lifter = if let Some(ret_type) = func.return_type() {
-            match ret_type {
-                uniffi_bindgen::interface::Type::Object { .. } => {
-                    quote!((ptr) => $lifter(Pointer<Void>.fromAddress(ptr)))
-                }
-                _ => lifter.clone(),
-            }
-        } else {
-            lifter.clone()
-        };
-
         quote!(
+            $doc
             Future<$ret> $(DartCodeOracle::fn_name(func.name()))($args) {
                 return uniffiRustCallAsync(
                   () => $(DartCodeOracle::find_lib_instance()).$(func.ffi_func().name())(
                     uniffiClonePointer(),
-                    $(for arg in &func.arguments() => $(DartCodeOracle::lower_arg_with_callback_handling(arg)),)
+                    $(for arg in &func.arguments() => $(DartCodeOracle::lower_arg_with_callback_handling(arg, type_helper.get_config())),)
                   ),
                   $(DartCodeOracle::async_poll(func, type_helper.get_ci())),
+                  $(DartCodeOracle::async_cancel(func, type_helper.get_ci())),
                   $(DartCodeOracle::async_complete(func, type_helper.get_ci())),
                   $(DartCodeOracle::async_free(func, type_helper.get_ci())),
-                  $async_lifter,
+                  $(DartCodeOracle::async_lift(func.return_type())),
                   $error_handler,
+                  cancellationToken,
+                  timeout,
                 );
             }
 
         )
     } else if ret == quote!(void) {
         quote!(
+            $(&doc)
             $ret $(DartCodeOracle::fn_name(func.name()))($args) {
-                return rustCall((status) {
+                return rustCall((uniffiStatus) {
                     $(DartCodeOracle::find_lib_instance()).$(func.ffi_func().name())(
                         uniffiClonePointer(),
-                        $(for arg in &func.arguments() => $(DartCodeOracle::lower_arg_with_callback_handling(arg)),) status
+                        $(for arg in &func.arguments() => $(DartCodeOracle::lower_arg_with_callback_handling(arg, type_helper.get_config())),) uniffiStatus
                     );
                 }, $error_handler);
             }
         )
     } else {
         quote!(
+            $(&doc)
             $ret $(DartCodeOracle::fn_name(func.name()))($args) {
-                return rustCall((status) => $lifter($(DartCodeOracle::find_lib_instance()).$(func.ffi_func().name())(
+                return rustCall((uniffiStatus) => $lifter($(DartCodeOracle::find_lib_instance()).$(func.ffi_func().name())(
                     uniffiClonePointer(),
-                    $(for arg in &func.arguments() => $(DartCodeOracle::lower_arg_with_callback_handling(arg)),) status
+                    $(for arg in &func.arguments() => $(DartCodeOracle::lower_arg_with_callback_handling(arg, type_helper.get_config())),) uniffiStatus
                 )), $error_handler);
             }
         )
@@ -475,19 +503,19 @@ fn trait_method_call(
         type_helper.include_once_check(&ret.as_codetype().canonical_name(), ret);
         let lifter = quote!($(ret.as_codetype().lift()));
         quote!(
-            rustCall((status) => $lifter($lib_instance.$ffi_name(
+            rustCall((uniffiStatus) => $lifter($lib_instance.$ffi_name(
                 uniffiClonePointer(),
                 $(for arg in lowered_args => $arg,)
-                status
+                uniffiStatus
             )), $error_handler)
         )
     } else {
         quote!(
-            rustCall((status) {
+            rustCall((uniffiStatus) {
                 $lib_instance.$ffi_name(
                     uniffiClonePointer(),
                     $(for arg in lowered_args => $arg,)
-                    status
+                    uniffiStatus
                 );
             }, $error_handler)
         )
@@ -515,6 +543,7 @@ fn generate_trait_object(obj: &Object, type_helper: &dyn TypeHelperRenderer) ->
         .map(|method| generate_method(method, type_helper));
 
     quote! {
+        $(DartCodeOracle::doc_comment(obj.docstring()))
         abstract class $cls_name {
             factory $cls_name.lift(Pointer<Void> ptr) => $(&impl_name)._internal(ptr);
 
@@ -606,25 +635,16 @@ fn generate_interface_method(
     method: &Method,
     type_helper: &dyn TypeHelperRenderer,
 ) -> dart::Tokens {
-    let arg_tokens: Vec<dart::Tokens> = method
-        .arguments()
-        .into_iter()
-        .map(|arg| {
-            let ty = arg.as_renderable().render_type(&arg.as_type(), type_helper);
-            let name = DartCodeOracle::var_name(arg.name());
-            quote!($ty $name)
-        })
-        .collect();
-
-    let params = if arg_tokens.is_empty() {
-        quote!()
+    let params = if method.is_async() {
+        generate_async_parameters(&method.arguments(), type_helper)
     } else {
-        quote!($(for arg in arg_tokens.iter() join (, ) => $arg))
+        generate_parameters(&method.arguments(), type_helper)
     };
     let ret_type = method_return_type_tokens(method, type_helper);
     let method_name = DartCodeOracle::fn_name(method.name());
 
     quote!(
+        $(DartCodeOracle::doc_comment(method.docstring()))
         $ret_type $method_name($params);
     )
 }
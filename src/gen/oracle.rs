@@ -4,8 +4,9 @@ use heck::{ToLowerCamelCase, ToUpperCamelCase};
 use uniffi_bindgen::interface::ffi::ExternalFfiMetadata;
 use uniffi_bindgen::interface::{Argument, Object, ObjectImpl};
 
-use crate::gen::CodeType;
-use uniffi_bindgen::interface::{AsType, Callable, FfiType, Type};
+use crate::gen::{CodeType, Config, DartDefault};
+use anyhow::{bail, Context, Result};
+use uniffi_bindgen::interface::{AsType, Callable, DefaultValue, FfiType, Type};
 use uniffi_bindgen::ComponentInterface;
 
 use crate::gen::primitives;
@@ -20,6 +21,148 @@ impl DartCodeOracle {
         type_.clone().as_type().as_codetype()
     }
 
+    /// Render the Dart default expression of a field or argument of type `type_`.
+    pub fn default_value(
+        type_: &Type,
+        default: &DefaultValue,
+        config: &Config,
+    ) -> Result<DartDefault> {
+        match default {
+            DefaultValue::Default => Self::find(type_).default_value(config),
+            DefaultValue::Literal(literal) => Self::find(type_).literal(literal, config),
+        }
+    }
+
+    /// The default of `arg`, if it has one.
+    pub fn argument_default(arg: &Argument, config: &Config) -> Option<DartDefault> {
+        arg.default_value().map(|default| {
+            Self::default_value(&arg.as_type(), default, config)
+                .expect("default values are checked before generating")
+        })
+    }
+
+    /// Check that every default value in `ci` can be rendered, so unsupported ones are reported
+    /// before any Dart is generated.
+    pub fn check_default_values(ci: &ComponentInterface, config: &Config) -> Result<()> {
+        for record in ci.record_definitions() {
+            for field in record.fields() {
+                if let Some(default) = field.default_value() {
+                    Self::check_default_value(&field.as_type(), default, ci, config).with_context(
+                        || format!("Default of `{}.{}`", record.name(), field.name()),
+                    )?;
+                }
+            }
+        }
+
+        let functions = ci
+            .function_definitions()
+            .iter()
+            .map(|func| (func.name().to_string(), func.arguments()));
+        let methods = ci.object_definitions().iter().flat_map(|obj| {
+            let constructors = obj
+                .constructors()
+                .into_iter()
+                .map(|cons| (format!("{}.{}", obj.name(), cons.name()), cons.arguments()));
+            let methods = obj
+                .methods()
+                .into_iter()
+                .map(|meth| (format!("{}.{}", obj.name(), meth.name()), meth.arguments()));
+            constructors.chain(methods).collect::<Vec<_>>()
+        });
+        for (name, arguments) in functions.chain(methods) {
+            for arg in arguments {
+                if let Some(default) = arg.default_value() {
+                    Self::check_default_value(&arg.as_type(), default, ci, config).with_context(
+                        || format!("Default of argument `{}` of `{name}`", arg.name()),
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that no variant of a non-exhaustive enum clashes with the `unknown` variant the
+    /// bindings add to it.
+    pub fn check_non_exhaustive_enums(ci: &ComponentInterface) -> Result<()> {
+        for enum_ in ci.enum_definitions() {
+            if !enum_.is_non_exhaustive() {
+                continue;
+            }
+            if let Some(variant) = enum_
+                .variants()
+                .iter()
+                .find(|variant| variant.name().to_upper_camel_case() == "Unknown")
+            {
+                bail!(
+                    "Variant `{}` of non-exhaustive enum `{}` clashes with the generated unknown variant",
+                    variant.name(),
+                    enum_.name()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that async methods implemented in Dart only throw enum errors, the only kind their
+    /// bindings can hand back to Rust as a typed error.
+    pub fn check_async_callback_errors(ci: &ComponentInterface) -> Result<()> {
+        let callback_methods = ci
+            .callback_interface_definitions()
+            .iter()
+            .flat_map(|callback| {
+                callback
+                    .methods()
+                    .into_iter()
+                    .map(move |method| (callback.name(), method))
+            })
+            .collect::<Vec<_>>();
+        let trait_methods = ci
+            .object_definitions()
+            .iter()
+            .filter(|obj| obj.has_callback_interface())
+            .flat_map(|obj| {
+                obj.methods()
+                    .into_iter()
+                    .map(move |method| (obj.name(), method))
+            })
+            .collect::<Vec<_>>();
+        for (name, method) in callback_methods.into_iter().chain(trait_methods) {
+            if !method.is_async() {
+                continue;
+            }
+            if let Some(error_type @ Type::Object { .. }) = method.throws_type() {
+                bail!(
+                    "Async method `{name}.{}` implemented in Dart throws object `{}`, but only enum errors can be passed back to Rust",
+                    method.name(),
+                    error_type.name().unwrap_or_default()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn check_default_value(
+        type_: &Type,
+        default: &DefaultValue,
+        ci: &ComponentInterface,
+        config: &Config,
+    ) -> Result<()> {
+        Self::default_value(type_, default, config)?;
+        // A record's default is built from the defaults of its fields
+        if let (DefaultValue::Default, Type::Record { name, .. }) = (default, type_) {
+            if let Some(field) = ci
+                .get_record_definition(name)
+                .and_then(|record| record.fields().iter().find(|f| f.default_value().is_none()))
+            {
+                bail!(
+                    "`{name}` has no default, as its field `{}` has none",
+                    field.name()
+                );
+            }
+        }
+        Ok(())
+    }
+
     /// Sanitize a Dart identifier, appending an underscore if it's a reserved keyword.
     pub fn sanitize_identifier(id: &str) -> String {
         if Self::is_reserved_identifier(id) {
@@ -69,6 +212,43 @@ impl DartCodeOracle {
         Self::sanitize_identifier(&nm.to_lower_camel_case())
     }
 
+    /// Render a docstring from the component interface as `///` Dart doc comments, one per line.
+    pub fn doc_comment(docstring: Option<&str>) -> dart::Tokens {
+        let mut tokens = dart::Tokens::new();
+        for line in docstring.map(Self::docstring_lines).unwrap_or_default() {
+            tokens.push();
+            if line.is_empty() {
+                tokens.append("///");
+            } else {
+                tokens.append(format!("/// {line}"));
+            }
+        }
+        // Whatever follows the comment must start on a fresh line
+        tokens.push();
+        tokens
+    }
+
+    /// Split a docstring into lines without the indentation of the Rust source. Proc-macros trim
+    /// the first line only, so the common indentation is taken from the lines after it.
+    fn docstring_lines(docstring: &str) -> Vec<&str> {
+        let mut lines = docstring.lines();
+        let first = lines.next().map(str::trim);
+        let rest = lines.map(str::trim_end).collect::<Vec<_>>();
+        let indent = rest
+            .iter()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        first
+            .into_iter()
+            .chain(
+                rest.iter()
+                    .map(|line| line.get(indent..).unwrap_or_default()),
+            )
+            .collect()
+    }
+
     /// Get the idiomatic Dart rendering of an FFI callback function name
     fn ffi_callback_name(nm: &str) -> String {
         format!(
@@ -259,11 +439,28 @@ impl DartCodeOracle {
         }
     }
 
+    /// The lift function for the result of an async call, which hands object handles over as
+    /// plain integers.
+    pub fn async_lift(return_type: Option<&Type>) -> dart::Tokens {
+        match return_type {
+            Some(ty @ Type::Object { .. }) => {
+                quote!((handle) => $(ty.as_codetype().lift())(Pointer<Void>.fromAddress(handle)))
+            }
+            Some(ty) => quote!($(ty.as_codetype().lift())),
+            None => quote!((_) {}),
+        }
+    }
+
     pub fn async_poll(callable: impl Callable, ci: &ComponentInterface) -> dart::Tokens {
         let ffi_func = callable.ffi_rust_future_poll(ci);
         quote!($(Self::find_lib_instance()).$ffi_func)
     }
 
+    pub fn async_cancel(callable: impl Callable, ci: &ComponentInterface) -> dart::Tokens {
+        let ffi_func = callable.ffi_rust_future_cancel(ci);
+        quote!($(Self::find_lib_instance()).$ffi_func)
+    }
+
     pub fn async_complete(callable: impl Callable, ci: &ComponentInterface) -> dart::Tokens {
         let ffi_func = callable.ffi_rust_future_complete(ci);
         let call = quote!($(Self::find_lib_instance()).$ffi_func);
@@ -276,17 +473,17 @@ impl DartCodeOracle {
     }
 
     /// Get the idiomatic Dart rendering of a class name based on `Type`.
-    pub fn dart_type_label(type_: Option<&Type>) -> dart::Tokens {
+    pub fn dart_type_label(type_: Option<&Type>, config: &Config) -> dart::Tokens {
         if let Some(ret_type) = type_ {
             match ret_type {
                 Type::UInt8
                 | Type::UInt16
                 | Type::UInt32
-                | Type::UInt64
                 | Type::Int8
                 | Type::Int16
                 | Type::Int32
                 | Type::Int64 => quote!(int),
+                Type::UInt64 => quote!($(Self::find(ret_type).type_label(config))),
                 Type::Float32 | Type::Float64 => quote!(double),
                 Type::Boolean => quote!(bool),
                 Type::Bytes => quote!(Uint8List),
@@ -299,11 +496,18 @@ impl DartCodeOracle {
                     quote!($class_name)
                 }
                 Type::Optional { inner_type } => {
-                    let inner = DartCodeOracle::dart_type_label(Some(inner_type));
-                    quote!($inner?)
+                    let inner = DartCodeOracle::dart_type_label(Some(inner_type), config);
+                    if compounds::is_nested_optional(ret_type, config) {
+                        quote!(Optional<$inner>)
+                    } else {
+                        quote!($inner?)
+                    }
+                }
+                Type::Sequence { .. } if compounds::typed_data_list(ret_type, config).is_some() => {
+                    quote!($(Self::find(ret_type).type_label(config)))
                 }
                 Type::Sequence { inner_type } => {
-                    let inner = DartCodeOracle::dart_type_label(Some(inner_type));
+                    let inner = DartCodeOracle::dart_type_label(Some(inner_type), config);
                     quote!(List<$inner>)
                 }
                 Type::Map {
@@ -311,8 +515,8 @@ impl DartCodeOracle {
                     value_type,
                     ..
                 } => {
-                    let key = DartCodeOracle::dart_type_label(Some(key_type));
-                    let value = DartCodeOracle::dart_type_label(Some(value_type));
+                    let key = DartCodeOracle::dart_type_label(Some(key_type), config);
+                    let value = DartCodeOracle::dart_type_label(Some(value_type), config);
                     quote!(Map<$key, $value>)
                 }
                 Type::Enum { name, .. } => {
@@ -354,8 +558,8 @@ impl DartCodeOracle {
                 Type::Boolean => quote!(Int8),
                 Type::Bytes => quote!(RustBuffer),
                 Type::String => quote!(RustBuffer),
-                Type::Timestamp => quote!(Int64),
-                Type::Duration => quote!(Int64),
+                Type::Timestamp => quote!(RustBuffer),
+                Type::Duration => quote!(RustBuffer),
                 Type::Optional { inner_type } => match **inner_type {
                     Type::String => quote!(RustBuffer),
                     _ => quote!(RustBuffer),
@@ -367,10 +571,8 @@ impl DartCodeOracle {
                 Type::Record { module_path, .. } => {
                     Self::rust_buffer_name_with_path(module_path, ci)
                 }
-                Type::Custom { name, .. } => {
-                    let class_name = &DartCodeOracle::class_name(name);
-                    quote!($class_name)
-                }
+                // Custom types cross the FFI as their builtin type
+                Type::Custom { builtin, .. } => Self::native_type_label(Some(builtin), ci),
                 _ => quote!(Pointer<Void>),
             }
         } else {
@@ -398,8 +600,8 @@ impl DartCodeOracle {
                 Type::Boolean => quote!(int),
                 Type::Bytes => quote!(RustBuffer),
                 Type::String => quote!(RustBuffer),
-                Type::Timestamp => quote!(int),
-                Type::Duration => quote!(int),
+                Type::Timestamp => quote!(RustBuffer),
+                Type::Duration => quote!(RustBuffer),
                 Type::Optional { inner_type } => match **inner_type {
                     Type::String => quote!(RustBuffer),
                     _ => quote!(RustBuffer),
@@ -411,10 +613,7 @@ impl DartCodeOracle {
                 Type::Record { module_path, .. } => {
                     Self::rust_buffer_name_with_path(module_path, ci)
                 }
-                Type::Custom { name, .. } => {
-                    let type_name = &DartCodeOracle::class_name(name);
-                    quote!($type_name)
-                }
+                Type::Custom { builtin, .. } => Self::native_dart_type_label(Some(builtin), ci),
                 _ => quote!(dynamic),
             }
         } else {
@@ -422,6 +621,14 @@ impl DartCodeOracle {
         }
     }
 
+    /// The type a value crosses the FFI as, looking through any custom types.
+    pub fn unwrap_custom(ty: &Type) -> &Type {
+        match ty {
+            Type::Custom { builtin, .. } => Self::unwrap_custom(builtin),
+            _ => ty,
+        }
+    }
+
     // Method to get the appropriate callback parameter type
     pub fn callback_param_type(
         arg_type: &Type,
@@ -446,34 +653,6 @@ impl DartCodeOracle {
                     outReturn.value = result ? 1 : 0;
                 )
             }
-            Type::Optional { inner_type } => {
-                // For optional return values
-                if let Type::String = **inner_type {
-                    quote!(
-                        final result = obj.$method_name($(for arg in &args => $arg,));
-                        if (result == null) {
-                            outReturn.ref = toRustBuffer(Uint8List.fromList([0]));
-                        } else {
-                            final lowered = FfiConverterOptionalString.lower(result);
-                            outReturn.ref = toRustBuffer(lowered.asUint8List());
-                        }
-                    )
-                } else {
-                    let lowered = ret_type.as_codetype().ffi_converter_name();
-                    quote!(
-                        final result = obj.$method_name($(for arg in &args => $arg,));
-                        if (result == null) {
-                            outReturn.ref = toRustBuffer(Uint8List.fromList([0]));
-                        } else {
-                            final lowered = $lowered.lower(result);
-                            final buffer = Uint8List(1 + lowered.len);
-                            buffer[0] = 1;
-                            buffer.setAll(1, lowered.asUint8List());
-                            outReturn.ref = toRustBuffer(buffer);
-                        }
-                    )
-                }
-            }
             Type::String => {
                 // For string return values
                 quote!(
@@ -482,13 +661,48 @@ impl DartCodeOracle {
                     status.code = CALL_SUCCESS;
                 )
             }
-            Type::Object { .. } => {
+            Type::Object { .. }
+            | Type::Int8
+            | Type::UInt8
+            | Type::Int16
+            | Type::UInt16
+            | Type::Int32
+            | Type::UInt32
+            | Type::Int64
+            | Type::UInt64
+            | Type::Float32
+            | Type::Float64 => {
                 let lowered = ret_type.as_codetype().ffi_converter_name();
                 quote!(
                     final result = obj.$method_name($(for arg in &args => $arg,));
                     outReturn.value = $lowered.lower(result);
                 )
             }
+            Type::Custom { .. } => {
+                // Lowered through the custom type's converter, into the builtin's out pointer
+                let lowered = ret_type.as_codetype().ffi_converter_name();
+                match Self::unwrap_custom(ret_type) {
+                    Type::Boolean
+                    | Type::Object { .. }
+                    | Type::Int8
+                    | Type::UInt8
+                    | Type::Int16
+                    | Type::UInt16
+                    | Type::Int32
+                    | Type::UInt32
+                    | Type::Int64
+                    | Type::UInt64
+                    | Type::Float32
+                    | Type::Float64 => quote!(
+                        final result = obj.$method_name($(for arg in &args => $arg,));
+                        outReturn.value = $lowered.lower(result);
+                    ),
+                    _ => quote!(
+                        final result = obj.$method_name($(for arg in &args => $arg,));
+                        outReturn.ref = $lowered.lower(result);
+                    ),
+                }
+            }
             Type::Sequence { inner_type } => {
                 if let Type::Int32 = **inner_type {
                     // For int32 sequence return values
@@ -519,9 +733,22 @@ impl DartCodeOracle {
     // Method to get the appropriate return type for callback functions
     pub fn callback_out_return_type(ret_type: Option<&Type>) -> dart::Tokens {
         if let Some(ret) = ret_type {
-            match ret {
+            match Self::unwrap_custom(ret) {
                 Type::Boolean => quote!(Pointer<Int8>),
                 Type::Object { .. } => quote!(Pointer<Pointer<Void>>),
+                // Numbers are written straight into the out pointer
+                ty @ (Type::Int8
+                | Type::UInt8
+                | Type::Int16
+                | Type::UInt16
+                | Type::Int32
+                | Type::UInt32
+                | Type::Int64
+                | Type::UInt64
+                | Type::Float32
+                | Type::Float64) => {
+                    quote!(Pointer<$(Self::ffi_type_label_by_reference(&FfiType::from(ty)))>)
+                }
                 _ => quote!(Pointer<RustBuffer>),
             }
         } else {
@@ -546,7 +773,7 @@ impl DartCodeOracle {
         // Use index-based variable names to avoid conflicts
         if let Type::Boolean = arg_type {
             quote!(final bool_arg$(arg_idx) = $arg_name == 1;)
-        } else if let Type::Enum { .. } = arg_type {
+        } else if let Type::Enum { .. } = Self::unwrap_custom(arg_type) {
             let converter = arg_type.as_codetype().ffi_converter_name();
             quote!(final arg$(arg_idx) = $converter.read(createUint8ListFromInt($arg_name)).value;)
         } else if let Type::Bytes = arg_type {
@@ -583,8 +810,14 @@ impl DartCodeOracle {
     }
 
     /// Lower argument with special handling for callback traits
-    pub fn lower_arg_with_callback_handling(arg: &Argument) -> dart::Tokens {
-        let base_lower = Self::type_lower_fn(&arg.as_type(), quote!($(Self::var_name(arg.name()))));
+    pub fn lower_arg_with_callback_handling(arg: &Argument, config: &Config) -> dart::Tokens {
+        let name = Self::var_name(arg.name());
+        // Arguments without a constant default are nullable, with `null` standing for the default
+        let value = match Self::argument_default(arg, config) {
+            Some(DartDefault::Runtime(default)) => quote!(($name ?? $default)),
+            _ => quote!($name),
+        };
+        let base_lower = Self::type_lower_fn(&arg.as_type(), value);
         match arg.as_type() {
             Type::Object {
                 imp: ObjectImpl::CallbackTrait,
@@ -706,6 +939,7 @@ impl<T: AsType> AsCodeType for T {
             Type::Boolean => Box::new(primitives::BooleanCodeType),
             Type::String => Box::new(primitives::StringCodeType),
             Type::Duration => Box::new(primitives::DurationCodeType),
+            Type::Timestamp => Box::new(primitives::TimestampCodeType),
             Type::Bytes => Box::new(primitives::BytesCodeType),
             Type::Object { name, imp, .. } => Box::new(objects::ObjectCodeType::new(name, imp)),
             Type::Optional { inner_type } => Box::new(compounds::OptionalCodeType::new(
@@ -735,7 +969,6 @@ impl<T: AsType> AsCodeType for T {
                 module_path,
                 builtin,
             } => Box::new(custom::CustomCodeType::new(name, module_path, builtin)),
-            _ => todo!("As Type for Type::{:?}", self.as_type()),
         }
     }
 }
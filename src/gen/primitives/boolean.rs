@@ -27,7 +27,7 @@ impl Renderable for BooleanCodeType {
                 }
 
                 static RustBuffer lowerIntoRustBuffer( bool value) {
-                    return toRustBuffer(Uint8List.fromList([FfiConverterBool.lower(value)]));
+                    return uniffiLowerIntoRustBuffer(1, (buf) => write(value, buf));
                 }
 
                 static int allocationSize([bool value = false]) {
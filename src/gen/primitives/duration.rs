@@ -13,13 +13,11 @@ impl Renderable for DurationCodeType {
         quote! {
             class FfiConverterDuration {
                 static Duration lift( RustBuffer buf) {
-                    return FfiConverterDuration.read(buf.asUint8List()).value;
+                    return uniffiLiftRustBuffer(buf, FfiConverterDuration.read);
                 }
 
                 static RustBuffer lower( Duration value) {
-                    final buf = Uint8List(allocationSize(value));
-                    write(value, buf);
-                    return toRustBuffer(buf);
+                    return uniffiLowerIntoRustBuffer(allocationSize(value), (buf) => write(value, buf));
                 }
 
                 static LiftRetVal<Duration> read( Uint8List buf) {
@@ -5,12 +5,31 @@ macro_rules! impl_code_type_for_primitive {
             pub struct $T;
 
             impl crate::gen::CodeType for $T  {
-                fn type_label(&self,) -> String {
+                fn type_label(&self, _config: &$crate::gen::Config) -> String {
                     $class_name.into()
                 }
 
-                fn literal(&self, literal: &uniffi_bindgen::pipeline::general::nodes::Literal) -> String {
-                    $crate::gen::primitives::render_literal(&literal)
+                fn literal(&self, literal: &uniffi_bindgen::interface::Literal, _config: &$crate::gen::Config) -> anyhow::Result<$crate::gen::DartDefault> {
+                    match literal {
+                        uniffi_bindgen::interface::Literal::EmptySequence if $class_name == "Uint8List" => {
+                            Ok($crate::gen::DartDefault::Runtime("Uint8List(0)".into()))
+                        }
+                        _ => $crate::gen::primitives::render_literal(&literal).map($crate::gen::DartDefault::Const),
+                    }
+                }
+
+                fn default_value(&self, _config: &$crate::gen::Config) -> anyhow::Result<$crate::gen::DartDefault> {
+                    use $crate::gen::DartDefault::{Const, Runtime};
+                    Ok(match $class_name {
+                        "int" => Const("0".into()),
+                        "double" => Const("0.0".into()),
+                        "bool" => Const("false".into()),
+                        "Duration" => Const("Duration.zero".into()),
+                        // `Uint8List` and `DateTime` have no constant values
+                        "Uint8List" => Runtime("Uint8List(0)".into()),
+                        "DateTime" => Runtime("DateTime.fromMillisecondsSinceEpoch(0, isUtc: true)".into()),
+                        _ => anyhow::bail!("{} has no default value", $class_name),
+                    })
                 }
 
                 fn canonical_name(&self,) -> String {
@@ -28,26 +47,37 @@ macro_rules! impl_code_type_for_primitive {
 macro_rules! impl_renderable_for_primitive {
     (BytesCodeType, $class_name:literal, $canonical_name:literal) => {
         impl Renderable for BytesCodeType {
-            fn render_type_helper(&self, _type_helper: &dyn TypeHelperRenderer) -> dart::Tokens {
+            fn render_type_helper(&self, type_helper: &dyn TypeHelperRenderer) -> dart::Tokens {
                 let cl_name = &self.ffi_converter_name();
-                let type_signature = &self.type_label();
+                let type_signature = &self.type_label(type_helper.get_config());
+
+                // Zero-copy bytes hand the `RustBuffer` over to the lifted list, which frees it
+                // once collected.
+                let lift = if type_helper.get_config().zero_copy_bytes() {
+                    quote! {
+                        final length = ByteData.sublistView(value.asUint8List()).getInt32(0);
+                        return uniffiRustBufferView(value, 4, length);
+                    }
+                } else {
+                    quote! {
+                        return uniffiLiftRustBuffer(value, $cl_name.read);
+                    }
+                };
 
                 quote! {
                     class $cl_name {
                         static $type_signature lift(RustBuffer value) {
-                            return $cl_name.read(value.asUint8List()).value;
+                            $lift
                         }
 
                         static LiftRetVal<$type_signature> read(Uint8List buf) {
                             final length = buf.buffer.asByteData(buf.offsetInBytes).getInt32(0);
-                            final bytes = Uint8List.view(buf.buffer, buf.offsetInBytes + 4, length);
+                            final bytes = Uint8List.fromList(Uint8List.view(buf.buffer, buf.offsetInBytes + 4, length));
                             return LiftRetVal(bytes, length + 4);
                         }
 
                         static RustBuffer lower($type_signature value) {
-                            final buf = Uint8List(allocationSize(value));
-                            write(value, buf);
-                            return toRustBuffer(buf);
+                            return uniffiLowerIntoRustBuffer(allocationSize(value), (buf) => write(value, buf));
                         }
 
                         static int allocationSize([$type_signature? value]) {
@@ -70,7 +100,7 @@ macro_rules! impl_renderable_for_primitive {
     };
     ($T:ty, $class_name:literal, $canonical_name:literal, $allocation_size:literal) => {
         impl Renderable for $T {
-            fn render_type_helper(&self, _type_helper: &dyn TypeHelperRenderer) -> dart::Tokens {
+            fn render_type_helper(&self, type_helper: &dyn TypeHelperRenderer) -> dart::Tokens {
                 use crate::gen::code_type::CodeType;
                 let endian = (if $canonical_name.contains("Float") {
                     ", Endian.little"
@@ -79,7 +109,7 @@ macro_rules! impl_renderable_for_primitive {
                 });
 
                 let cl_name = &self.ffi_converter_name();
-                let type_signature = &self.type_label();
+                let type_signature = &self.type_label(type_helper.get_config());
                 let conversion_name = &$canonical_name
                                     .replace("UInt", "Uint")
                                     .replace("Double", "Float");
@@ -113,9 +143,9 @@ macro_rules! impl_renderable_for_primitive {
     };
     ($T:ty, $class_name:literal, $canonical_name:literal, $allocation_size:literal, $min_value:literal, $max_value:literal, $type_name:literal) => {
         impl Renderable for $T {
-            fn render_type_helper(&self, _type_helper: &dyn TypeHelperRenderer) -> dart::Tokens {
+            fn render_type_helper(&self, type_helper: &dyn TypeHelperRenderer) -> dart::Tokens {
                 let cl_name = &self.ffi_converter_name();
-                let type_signature = &self.type_label();
+                let type_signature = &self.type_label(type_helper.get_config());
                 let conversion_name = &$canonical_name
                     .replace("UInt", "Uint")
                     .replace("Double", "Float");
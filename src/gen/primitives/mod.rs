@@ -3,55 +3,64 @@ mod macros;
 mod boolean;
 mod duration;
 mod string;
+mod timestamp;
 
 use crate::gen::render::{Renderable, TypeHelperRenderer};
-use crate::gen::CodeType;
+use crate::gen::{CodeType, Config, DartDefault};
+use anyhow::{bail, Result};
 use genco::prelude::*;
 use paste::paste;
-use uniffi_bindgen::pipeline::general::nodes::{Literal, Radix, Type, TypeNode};
+use uniffi_bindgen::interface::{Literal, Radix, Type};
 
 pub use boolean::BooleanCodeType;
 pub use duration::DurationCodeType;
 pub use string::StringCodeType;
+pub use timestamp::TimestampCodeType;
 
-fn render_literal(literal: &Literal) -> String {
-    fn typed_number(type_node: &TypeNode, num_str: String) -> String {
-        match &type_node.ty {
+fn render_literal(literal: &Literal) -> Result<String> {
+    fn typed_number(type_: &Type, num_str: String) -> Result<String> {
+        match type_ {
             Type::Int8
             | Type::UInt8
             | Type::Int16
             | Type::UInt16
             | Type::Int32
             | Type::UInt32
+            | Type::Int64
             | Type::UInt64
             | Type::Float32
             | Type::Float64
-            | Type::Duration => num_str,
-            _ => panic!("Unexpected literal: {num_str} is not a number"),
+            | Type::Duration => Ok(num_str),
+            _ => bail!("Unexpected literal: {num_str} is not a number"),
         }
     }
 
     match literal {
-        Literal::Boolean(v) => format!("{v}"),
-        Literal::String(s) => format!("'{s}'"),
-        Literal::Int(i, radix, type_node) => typed_number(
-            type_node,
+        Literal::Boolean(v) => Ok(format!("{v}")),
+        Literal::String(s) => Ok(format!(
+            "'{}'",
+            s.replace('\\', "\\\\")
+                .replace('\'', "\\'")
+                .replace('$', "\\$")
+        )),
+        Literal::Int(i, radix, type_) => typed_number(
+            type_,
             match radix {
                 Radix::Octal => format!("{i:#x}"),
                 Radix::Decimal => format!("{i}"),
                 Radix::Hexadecimal => format!("{i:#x}"),
             },
         ),
-        Literal::UInt(i, radix, type_node) => typed_number(
-            type_node,
+        Literal::UInt(i, radix, type_) => typed_number(
+            type_,
             match radix {
                 Radix::Octal => format!("{i:#x}"),
                 Radix::Decimal => format!("{i}"),
                 Radix::Hexadecimal => format!("{i:#x}"),
             },
         ),
-        Literal::Float(string, type_node) => typed_number(type_node, string.clone()),
-        _ => unreachable!("Literal"),
+        Literal::Float(string, type_) => typed_number(type_, string.clone()),
+        _ => bail!("Unexpected literal: {literal:?} is not a primitive value"),
     }
 }
 
@@ -63,7 +72,6 @@ impl_code_type_for_primitive!(Int64CodeType, "int", "Int64");
 impl_code_type_for_primitive!(UInt8CodeType, "int", "UInt8");
 impl_code_type_for_primitive!(UInt16CodeType, "int", "UInt16");
 impl_code_type_for_primitive!(UInt32CodeType, "int", "UInt32");
-impl_code_type_for_primitive!(UInt64CodeType, "int", "UInt64");
 impl_code_type_for_primitive!(Float32CodeType, "double", "Double32");
 impl_code_type_for_primitive!(Float64CodeType, "double", "Double64");
 
@@ -94,11 +102,73 @@ impl_renderable_for_primitive!(UInt32CodeType, "int", "UInt32", 4, 0, 4294967295
 impl_renderable_for_primitive!(Float32CodeType, "double", "Double32", 4);
 impl_renderable_for_primitive!(Float64CodeType, "double", "Double64", 8);
 
-// Keep u64 on the legacy int path for now; full upper-bound validation lands with BigInt support.
+#[derive(Debug)]
+pub struct UInt64CodeType;
+
+impl CodeType for UInt64CodeType {
+    fn type_label(&self, config: &Config) -> String {
+        if config.uint64_as_bigint() {
+            "BigInt".into()
+        } else {
+            "int".into()
+        }
+    }
+
+    // `BigInt` has no constant values, so its defaults are built when they are used
+    fn literal(&self, literal: &Literal, config: &Config) -> Result<DartDefault> {
+        let value = render_literal(literal)?;
+        Ok(match literal {
+            _ if !config.uint64_as_bigint() => DartDefault::Const(value),
+            // Too large for a Dart `int` literal
+            Literal::UInt(v, ..) if *v > i64::MAX as u64 => {
+                DartDefault::Runtime(format!("BigInt.parse(\"{v}\")"))
+            }
+            _ => DartDefault::Runtime(format!("BigInt.from({value})")),
+        })
+    }
+
+    fn default_value(&self, config: &Config) -> Result<DartDefault> {
+        Ok(if config.uint64_as_bigint() {
+            DartDefault::Runtime("BigInt.zero".into())
+        } else {
+            DartDefault::Const("0".into())
+        })
+    }
+
+    fn canonical_name(&self) -> String {
+        "UInt64".into()
+    }
+}
+
+// As a Dart `int`, a `u64` above `i64::MAX` comes back negative; `BigInt` covers the full range.
+// Either way it crosses the FFI as an `int` holding the `u64`'s bits.
 impl Renderable for UInt64CodeType {
-    fn render_type_helper(&self, _type_helper: &dyn TypeHelperRenderer) -> dart::Tokens {
+    fn render_type_helper(&self, type_helper: &dyn TypeHelperRenderer) -> dart::Tokens {
         let cl_name = &self.ffi_converter_name();
-        let type_signature = &self.type_label();
+        let type_signature = &self.type_label(type_helper.get_config());
+
+        if type_helper.get_config().uint64_as_bigint() {
+            return quote! {
+                class $cl_name {
+                    static BigInt lift(int value) => uniffiUInt64ToBigInt(value);
+
+                    static LiftRetVal<BigInt> read(Uint8List buf) {
+                        return LiftRetVal(lift(buf.buffer.asByteData(buf.offsetInBytes).getUint64(0)), 8);
+                    }
+
+                    static int lower(BigInt value) => uniffiBigIntToUInt64(value);
+
+                    static int allocationSize([BigInt? value]) {
+                        return 8;
+                    }
+
+                    static int write(BigInt value, Uint8List buf) {
+                        buf.buffer.asByteData(buf.offsetInBytes).setUint64(0, lower(value));
+                        return 8;
+                    }
+                }
+            };
+        }
 
         quote! {
             class $cl_name {
@@ -3,15 +3,29 @@ use crate::gen::{
     render::{Renderable, TypeHelperRenderer},
 };
 
-use crate::gen::CodeType;
+use crate::gen::{CodeType, Config, DartDefault};
+use anyhow::Result;
 use genco::lang::dart;
+use uniffi_bindgen::interface::Literal;
 
 #[derive(Debug)]
 pub struct StringCodeType;
 impl CodeType for StringCodeType {
-    fn type_label(&self) -> String {
+    fn type_label(&self, _config: &Config) -> String {
         "String".to_owned()
     }
+
+    fn canonical_name(&self) -> String {
+        "String".to_owned()
+    }
+
+    fn literal(&self, literal: &Literal, _config: &Config) -> Result<DartDefault> {
+        super::render_literal(literal).map(DartDefault::Const)
+    }
+
+    fn default_value(&self, _config: &Config) -> Result<DartDefault> {
+        Ok(DartDefault::Const("''".to_owned()))
+    }
 }
 
 impl Renderable for StringCodeType {
@@ -20,11 +34,15 @@ impl Renderable for StringCodeType {
             class FfiConverterString {
                 static String lift( RustBuffer buf) {
                     // reading the entire buffer, the len is where the string finishes
-                    return utf8.decoder.convert(buf.asUint8List());
+                    try {
+                        return utf8.decoder.convert(buf.asUint8List());
+                    } finally {
+                        buf.free();
+                    }
                 }
 
                 static RustBuffer lower( String value) {
-                    return toRustBuffer(Utf8Encoder().convert(value));
+                    return toRustBuffer(utf8.encode(value));
                 }
 
                 static LiftRetVal<String> read( Uint8List buf) {
@@ -33,13 +51,11 @@ impl Renderable for StringCodeType {
                 }
 
                 static int allocationSize([String value = ""]) {
-                    // FIXME: doing this twice for every string is bad
-                    return utf8.encoder.convert(value).length + 4; // Four additional bytes for the length data
+                    return uniffiUtf8Length(value) + 4; // Four additional bytes for the length data
                 }
 
                 static int write( String value, Uint8List buf) {
-                    // two memcopies feels bad :(
-                    final list = utf8.encoder.convert(value);
+                    final list = utf8.encode(value);
                     buf.buffer.asByteData(buf.offsetInBytes).setInt32(0, list.length);
                     buf.setAll(4, list);
                     return list.length + 4;
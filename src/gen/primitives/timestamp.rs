@@ -0,0 +1,57 @@
+use crate::gen::{
+    quote,
+    render::{Renderable, TypeHelperRenderer},
+};
+
+use super::paste;
+use genco::lang::dart;
+
+impl_code_type_for_primitive!(TimestampCodeType, "DateTime", "Timestamp");
+
+impl Renderable for TimestampCodeType {
+    fn render_type_helper(&self, _type_helper: &dyn TypeHelperRenderer) -> dart::Tokens {
+        // Timestamps are serialized as signed seconds and unsigned nanoseconds relative to the
+        // Unix epoch. For pre-epoch values the sign applies to the whole offset, so the
+        // nanoseconds move the timestamp further away from the epoch.
+        quote! {
+            class FfiConverterTimestamp {
+                static DateTime lift( RustBuffer buf) {
+                    return uniffiLiftRustBuffer(buf, FfiConverterTimestamp.read);
+                }
+
+                static RustBuffer lower( DateTime value) {
+                    return uniffiLowerIntoRustBuffer(allocationSize(value), (buf) => write(value, buf));
+                }
+
+                static LiftRetVal<DateTime> read( Uint8List buf) {
+                    final bytes = buf.buffer.asByteData(buf.offsetInBytes, 12);
+                    final seconds = bytes.getInt64(0);
+                    final nanos = bytes.getUint32(8);
+                    // DateTime spans 100,000,000 days either side of the epoch
+                    if (seconds.abs() > 8640000000000) {
+                        throw RangeError.range(seconds, -8640000000000, 8640000000000, "seconds", "Timestamp outside the DateTime range");
+                    }
+                    final micros = seconds.abs() * 1000000 + nanos ~/ 1000;
+                    return LiftRetVal(
+                        DateTime.fromMicrosecondsSinceEpoch(seconds >= 0 ? micros : -micros, isUtc: true),
+                        12,
+                    );
+                }
+
+                static int allocationSize([DateTime? value]) {
+                    return 12;
+                }
+
+                static int write( DateTime value, Uint8List buf) {
+                    final bytes = buf.buffer.asByteData(buf.offsetInBytes, 12);
+                    final micros = value.microsecondsSinceEpoch;
+                    final sign = micros < 0 ? -1 : 1;
+                    final offset = micros.abs();
+                    bytes.setInt64(0, sign * (offset ~/ 1000000));
+                    bytes.setUint32(8, (offset % 1000000) * 1000);
+                    return 12;
+                }
+            }
+        }
+    }
+}
@@ -1,10 +1,11 @@
+use super::compounds::is_nested_optional;
 use super::oracle::{AsCodeType, DartCodeOracle};
 use super::render::{Renderable, TypeHelperRenderer};
 use super::types::generate_type;
-use crate::gen::CodeType;
+use crate::gen::{CodeType, Config, DartDefault};
+use anyhow::{bail, Result};
 use genco::prelude::*;
-use uniffi_bindgen::interface::{AsType, Record};
-use uniffi_bindgen::pipeline::general::nodes::Literal;
+use uniffi_bindgen::interface::{AsType, Literal, Record, Type};
 
 #[derive(Debug)]
 pub struct RecordCodeType {
@@ -21,7 +22,7 @@ impl RecordCodeType {
 }
 
 impl CodeType for RecordCodeType {
-    fn type_label(&self) -> String {
+    fn type_label(&self, _config: &Config) -> String {
         DartCodeOracle::class_name(&self.id)
     }
 
@@ -29,8 +30,16 @@ impl CodeType for RecordCodeType {
         self.id.to_string()
     }
 
-    fn literal(&self, _literal: &Literal) -> String {
-        todo!("literal not implemented for RecordCodeType");
+    fn literal(&self, literal: &Literal, _config: &Config) -> Result<DartDefault> {
+        bail!("Records have no literal representation, only `#[uniffi(default)]`, not {literal:?}");
+    }
+
+    // Built from the defaults of its own fields, so every field needs one
+    fn default_value(&self, config: &Config) -> Result<DartDefault> {
+        Ok(DartDefault::Runtime(format!(
+            "{}()",
+            self.type_label(config)
+        )))
     }
 }
 
@@ -47,21 +56,76 @@ impl Renderable for RecordCodeType {
 }
 
 pub fn generate_record(obj: &Record, type_helper: &dyn TypeHelperRenderer) -> dart::Tokens {
+    let config = type_helper.get_config();
     let cls_name = &DartCodeOracle::class_name(obj.name());
     let ffi_conv_name = &DartCodeOracle::class_name(&obj.as_codetype().ffi_converter_name());
     for f in obj.fields() {
         type_helper.include_once_check(&f.as_codetype().canonical_name(), &f.as_type());
     }
+    let value_fields = obj
+        .fields()
+        .iter()
+        .map(|f| {
+            ValueField::new(
+                DartCodeOracle::var_name(f.name()),
+                generate_type(&f.as_type(), config),
+                &f.as_type(),
+                config,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    // Records with any defaulted field use named parameters, so the defaults can be omitted.
+    let named_args = obj.fields().iter().any(|f| f.default_value().is_some());
+    let constructor: dart::Tokens = if named_args {
+        // Defaults that aren't constant are filled in by the initializer list
+        let mut initializers = Vec::new();
+        let params = obj
+            .fields()
+            .iter()
+            .map(|f| {
+                let name = DartCodeOracle::var_name(f.name());
+                let default = f.default_value().map(|default| {
+                    DartCodeOracle::default_value(&f.as_type(), default, config)
+                        .expect("default values are checked before generating")
+                });
+                match default {
+                    Some(DartDefault::Const(default)) => quote!(this.$name = $default),
+                    Some(DartDefault::Runtime(default)) => {
+                        initializers.push(quote!($(&name) = $(&name) ?? $default));
+                        quote!($(nullable_type(&f.as_type(), config)) $name)
+                    }
+                    None => quote!(required this.$name),
+                }
+            })
+            .collect::<Vec<_>>();
+        if initializers.is_empty() {
+            quote!($(cls_name)({$(for p in params join (, ) => $p)});)
+        } else {
+            quote!($(cls_name)({$(for p in params join (, ) => $p)}) : $(for i in initializers join (, ) => $i);)
+        }
+    } else {
+        quote!($(cls_name)($(for f in obj.fields() => this.$(DartCodeOracle::var_name(f.name())), ));)
+    };
+
     quote! {
+        $(DartCodeOracle::doc_comment(obj.docstring()))
         class $cls_name {
-            $(for f in obj.fields() => final $(generate_type(&f.as_type())) $(DartCodeOracle::var_name(f.name()));)
+            $(for f in obj.fields() =>
+                $(DartCodeOracle::doc_comment(f.docstring()))
+                final $(generate_type(&f.as_type(), config)) $(DartCodeOracle::var_name(f.name()));
+            )
 
-            $(cls_name)($(for f in obj.fields() => this.$(DartCodeOracle::var_name(f.name())), ));
+            $constructor
+
+            $(generate_value_methods(cls_name, quote!($cls_name), &value_fields, None, named_args))
+
+            $(generate_to_string(cls_name, &value_fields))
         }
 
         class $ffi_conv_name {
             static $cls_name lift( RustBuffer buf) {
-                return $ffi_conv_name.read(buf.asUint8List()).value;
+                return uniffiLiftRustBuffer(buf, $ffi_conv_name.read);
             }
 
             static LiftRetVal<$cls_name> read( Uint8List buf) {
@@ -73,15 +137,12 @@ pub fn generate_record(obj: &Record, type_helper: &dyn TypeHelperRenderer) -> da
                     new_offset += $(DartCodeOracle::var_name(f.name()))_lifted.bytesRead;
                 )
                 return LiftRetVal($(cls_name)(
-                    $(for f in obj.fields() => $(DartCodeOracle::var_name(f.name())),)
+                    $(for f in obj.fields() => $(if named_args => $(DartCodeOracle::var_name(f.name())): ) $(DartCodeOracle::var_name(f.name())),)
                 ), new_offset - buf.offsetInBytes);
             }
 
             static RustBuffer lower( $cls_name value) {
-                final total_length = $(for f in obj.fields() => $(f.as_type().as_codetype().ffi_converter_name()).allocationSize(value.$(DartCodeOracle::var_name(f.name()))) + ) 0;
-                final buf = Uint8List(total_length);
-                write(value, buf);
-                return toRustBuffer(buf);
+                return uniffiLowerIntoRustBuffer(allocationSize(value), (buf) => write(value, buf));
             }
 
             static int write( $cls_name value, Uint8List buf) {
@@ -99,3 +160,111 @@ pub fn generate_record(obj: &Record, type_helper: &dyn TypeHelperRenderer) -> da
         }
     }
 }
+
+/// Whether `ty` is a nullable Dart type. Nested optionals are `Optional`s instead.
+pub fn is_nullable(ty: &Type, config: &Config) -> bool {
+    matches!(ty, Type::Optional { .. }) && !is_nested_optional(ty, config)
+}
+
+/// The Dart type of `ty`, made nullable if it isn't already.
+pub fn nullable_type(ty: &Type, config: &Config) -> dart::Tokens {
+    if is_nullable(ty, config) {
+        generate_type(ty, config)
+    } else {
+        quote!($(generate_type(ty, config))?)
+    }
+}
+
+/// A field of a generated immutable class, as seen by `==`, `hashCode`, `toString` and `copyWith`.
+pub struct ValueField {
+    name: String,
+    type_label: dart::Tokens,
+    nullable: bool,
+}
+
+impl ValueField {
+    pub fn new(name: String, type_label: dart::Tokens, ty: &Type, config: &Config) -> Self {
+        Self {
+            name,
+            type_label,
+            nullable: is_nullable(ty, config),
+        }
+    }
+
+    // Nullable fields can't tell an omitted argument from `null` by the value alone
+    fn copy_with_param(&self) -> dart::Tokens {
+        let name = &self.name;
+        if self.nullable {
+            quote!(Object? $name = uniffiUnset)
+        } else {
+            quote!($(&self.type_label)? $name)
+        }
+    }
+
+    fn copy_with_value(&self) -> dart::Tokens {
+        let name = &self.name;
+        if self.nullable {
+            quote!(identical($name, uniffiUnset) ? this.$name : $name as $(&self.type_label))
+        } else {
+            quote!($name ?? this.$name)
+        }
+    }
+}
+
+/// Render structural `==`, `hashCode` and `copyWith` members for a generated class.
+///
+/// Fields are compared with the `uniffiDeepEquals` runtime helper, so nested `List` and `Map`
+/// values are compared by content. `hash_seed` lets sibling classes with identical fields, such as
+/// enum variants, hash differently. `named_args` selects how `copyWith` calls `constructor`.
+pub fn generate_value_methods(
+    cls_name: &str,
+    constructor: dart::Tokens,
+    fields: &[ValueField],
+    hash_seed: Option<usize>,
+    named_args: bool,
+) -> dart::Tokens {
+    let mut hash_parts: Vec<dart::Tokens> =
+        hash_seed.map(|seed| quote!($seed)).into_iter().collect();
+    hash_parts.extend(fields.iter().map(|f| quote!(uniffiDeepHash($(&f.name)))));
+
+    let copy_with = if fields.is_empty() {
+        quote!()
+    } else {
+        quote! {
+            $cls_name copyWith({$(for f in fields join (, ) => $(f.copy_with_param()))}) {
+                return $constructor($(for f in fields join (, ) => $(if named_args => $(&f.name): )$(f.copy_with_value())));
+            }
+        }
+    };
+
+    quote! {
+        @override
+        bool operator ==(Object other) {
+            if (identical(this, other)) {
+                return true;
+            }
+            return other is $cls_name $(for f in fields => && uniffiDeepEquals($(&f.name), other.$(&f.name)));
+        }
+
+        @override
+        int get hashCode => Object.hashAll([$(for part in hash_parts join (, ) => $part)]);
+
+        $copy_with
+    }
+}
+
+/// Render a `toString` listing every field by name, e.g. `Point(x: 1, y: 2)`.
+pub fn generate_to_string(display_name: &str, fields: &[ValueField]) -> dart::Tokens {
+    let rendered_fields = fields
+        .iter()
+        .map(|f| format!("{0}: ${{{0}}}", f.name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let to_string = format!("\"{display_name}({rendered_fields})\"");
+    quote! {
+        @override
+        String toString() {
+            return $(&to_string);
+        }
+    }
+}
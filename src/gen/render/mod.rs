@@ -4,12 +4,15 @@ use genco::{lang::dart, quote};
 use uniffi_bindgen::interface::{AsType, Enum, Object, Record, Type};
 use uniffi_bindgen::ComponentInterface;
 
+use super::Config;
+
 pub trait Renderer<T> {
     fn render(&self) -> T;
 }
 
 pub trait TypeHelperRenderer {
     fn get_ci(&self) -> &ComponentInterface;
+    fn get_config(&self) -> &Config;
     fn include_once_check(&self, name: &str, ty: &Type) -> bool;
     fn check(&self, name: &str) -> bool;
 
@@ -22,6 +25,7 @@ pub trait Renderable {
     fn render_type(&self, ty: &Type, type_helper: &dyn TypeHelperRenderer) -> dart::Tokens {
         use super::oracle::DartCodeOracle;
 
+        let config = type_helper.get_config();
         let type_name = match ty {
             Type::UInt8
             | Type::Int8
@@ -29,14 +33,20 @@ pub trait Renderable {
             | Type::Int16
             | Type::UInt32
             | Type::Int32
-            | Type::UInt64
             | Type::Int64 => quote!(int),
+            Type::UInt64 => quote!($(DartCodeOracle::find(ty).type_label(config))),
             Type::Float32 | Type::Float64 => quote!(double),
             Type::String => quote!(String),
             Type::Boolean => quote!(bool),
             Type::Bytes => quote!(Uint8List),
             Type::Object { name, .. } => quote!($name),
+            Type::Optional { inner_type } if compounds::is_nested_optional(ty, config) => {
+                quote!(Optional<$(&self.render_type(inner_type, type_helper))>)
+            }
             Type::Optional { inner_type } => quote!($(&self.render_type(inner_type, type_helper))?),
+            Type::Sequence { .. } if compounds::typed_data_list(ty, config).is_some() => {
+                quote!($(DartCodeOracle::find(ty).type_label(config)))
+            }
             Type::Sequence { inner_type } => {
                 quote!(List<$(&self.render_type(inner_type, type_helper))>)
             }
@@ -49,9 +59,9 @@ pub trait Renderable {
             Type::Enum { name, .. } => quote!($(DartCodeOracle::class_name(name))),
             Type::Record { name, .. } => quote!($name),
             Type::Custom { name, .. } => quote!($name),
+            Type::Timestamp => quote!(DateTime),
             Type::Duration => quote!(Duration),
             Type::CallbackInterface { name, .. } => quote!($name),
-            _ => todo!("Type::{:?}", ty),
         };
 
         if !type_helper.include_once_check(&ty.as_codetype().canonical_name(), ty) {
@@ -84,6 +94,7 @@ impl<T: AsType> AsRenderable for T {
             Type::Boolean => Box::new(primitives::BooleanCodeType),
             Type::String => Box::new(primitives::StringCodeType),
             Type::Duration => Box::new(primitives::DurationCodeType),
+            Type::Timestamp => Box::new(primitives::TimestampCodeType),
             Type::Bytes => Box::new(primitives::BytesCodeType),
             Type::Object { name, imp, .. } => Box::new(objects::ObjectCodeType::new(name, imp)),
             Type::Optional { inner_type } => Box::new(compounds::OptionalCodeType::new(
@@ -112,7 +123,6 @@ impl<T: AsType> AsRenderable for T {
             Type::CallbackInterface { name, .. } => Box::new(
                 callback_interface::CallbackInterfaceCodeType::new(name, self.as_type()),
             ),
-            _ => todo!("Renderable for Type::{:?}", self.as_type()),
         }
     }
 }
@@ -6,19 +6,24 @@ use uniffi_bindgen::interface::AsType;
 use uniffi_bindgen::{interface::Type, ComponentInterface};
 
 use super::render::{AsRenderable, Renderable, Renderer, TypeHelperRenderer};
-use super::{enums, functions, objects, oracle::AsCodeType, records};
+use super::{
+    callback_interface, compounds, enums, functions, objects, oracle::AsCodeType, records,
+};
 use crate::gen::oracle::DartCodeOracle;
+use crate::gen::Config;
 
 type FunctionDefinition = dart::Tokens;
 
 pub struct TypeHelpersRenderer<'a> {
+    config: &'a Config,
     ci: &'a ComponentInterface,
     include_once_names: RefCell<HashMap<String, Type>>,
 }
 
 impl<'a> TypeHelpersRenderer<'a> {
-    pub fn new(ci: &'a ComponentInterface) -> Self {
+    pub fn new(config: &'a Config, ci: &'a ComponentInterface) -> Self {
         Self {
+            config,
             ci,
             include_once_names: RefCell::new(HashMap::new()),
         }
@@ -57,6 +62,10 @@ impl TypeHelperRenderer for TypeHelpersRenderer<'_> {
         self.ci
     }
 
+    fn get_config(&self) -> &Config {
+        self.config
+    }
+
     fn get_record(&self, name: &str) -> Option<&uniffi_bindgen::interface::Record> {
         self.ci.get_record_definition(name)
     }
@@ -91,6 +100,9 @@ impl Renderer<(FunctionDefinition, dart::Tokens)> for TypeHelpersRenderer<'_> {
                 $(format!("import \"{}.dart\"", imp));
                 $(format!("import \"{}.dart\"", imp)) as $imp;
             })
+            $( for imp in self.config.custom_type_imports() {
+                $(format!("import \"{}\"", imp));
+            })
         );
 
         // let function_definitions = quote!($( for fun in self.ci.function_definitions() => $(functions::generate_function("this", fun, self))));
@@ -106,7 +118,7 @@ impl Renderer<(FunctionDefinition, dart::Tokens)> for TypeHelpersRenderer<'_> {
         // Process all callback interfaces to ensure they're included
         for callback in self.ci.callback_interface_definitions() {
             let callback_name = callback.name().to_string();
-            let callback_codetype = super::callback_interface::CallbackInterfaceCodeType::new(
+            let callback_codetype = callback_interface::CallbackInterfaceCodeType::new(
                 callback_name,
                 callback.as_type(),
             );
@@ -121,6 +133,11 @@ impl Renderer<(FunctionDefinition, dart::Tokens)> for TypeHelpersRenderer<'_> {
             $(for (_, ty) in self.get_include_names().iter() => $(ty.as_renderable().render_type_helper(self)) )
         };
 
+        // Exported by the scaffolding from `uniffi_dart::generate_scaffolding`
+        let rustbuffer_finalize =
+            format!("\"ffi_{}_dart_rustbuffer_finalize\"", self.ci.namespace());
+        let rustbuffer_token = format!("\"ffi_{}_dart_rustbuffer_token\"", self.ci.namespace());
+
         let types_helper_code = quote! {
             import "dart:async";
             import "dart:convert";
@@ -184,6 +201,7 @@ impl Renderer<(FunctionDefinition, dart::Tokens)> for TypeHelpersRenderer<'_> {
             const int CALL_SUCCESS = 0;
             const int CALL_ERROR = 1;
             const int CALL_UNEXPECTED_ERROR = 2;
+            const int CALL_CANCELLED = 3;
 
             final class RustCallStatus extends Struct {
                 @Int8()
@@ -200,10 +218,13 @@ impl Renderer<(FunctionDefinition, dart::Tokens)> for TypeHelpersRenderer<'_> {
                 return;
                 } else if (status.ref.code == CALL_ERROR) {
                 throw errorHandler.lift(status.ref.errorBuf);
+                } else if (status.ref.code == CALL_CANCELLED) {
+                throw const UniffiCancelledException();
                 } else if (status.ref.code == CALL_UNEXPECTED_ERROR) {
                 if (status.ref.errorBuf.len > 0) {
                     throw UniffiInternalError.panicked(FfiConverterString.lift(status.ref.errorBuf));
                 } else {
+                    status.ref.errorBuf.free();
                     throw UniffiInternalError.panicked("Rust panic");
                 }
                 } else {
@@ -276,17 +297,24 @@ impl Renderer<(FunctionDefinition, dart::Tokens)> for TypeHelpersRenderer<'_> {
                 }
             }
 
-            RustBuffer toRustBuffer(Uint8List data) {
-                final length = data.length;
-
-                final Pointer<Uint8> frameData = calloc<Uint8>(length); // Allocate a pointer large enough.
-                final pointerList = frameData.asTypedList(length); // Create a list that uses our pointer and copy in the data.
-                pointerList.setAll(0, data); // FIXME: can we remove this memcopy somehow?
+            // Lowering allocates the `RustBuffer` on the Rust side up front and lets `write`
+            // serialize straight into it, so the value is copied exactly once.
+            RustBuffer uniffiLowerIntoRustBuffer(int size, int Function(Uint8List) write) {
+                final buf = RustBuffer.alloc(size);
+                try {
+                    write(buf.asUint8List());
+                    return buf;
+                } catch (_) {
+                    buf.free();
+                    rethrow;
+                }
+            }
 
-                final bytes = calloc<ForeignBytes>();
-                bytes.ref.len = length;
-                bytes.ref.data = frameData;
-                return RustBuffer.fromBytes(bytes.ref);
+            RustBuffer toRustBuffer(Uint8List data) {
+                return uniffiLowerIntoRustBuffer(data.length, (buf) {
+                    buf.setAll(0, data);
+                    return data.length;
+                });
             }
 
             final class ForeignBytes extends Struct {
@@ -308,6 +336,20 @@ impl Renderer<(FunctionDefinition, dart::Tokens)> for TypeHelpersRenderer<'_> {
                 }
             }
 
+            // Buffer ownership:
+            // - a `RustBuffer` produced by `lower` is handed over to Rust, which frees it;
+            // - a `RustBuffer` received from Rust (return values, callback arguments and
+            //   error buffers) belongs to Dart and is freed by the `lift` consuming it;
+            // - `read` and `write` only borrow the bytes they are given, so anything
+            //   they return must not point into the buffer.
+            T uniffiLiftRustBuffer<T>(RustBuffer buf, LiftRetVal<T> Function(Uint8List) read) {
+                try {
+                    return read(buf.asUint8List()).value;
+                } finally {
+                    buf.free();
+                }
+            }
+
             class LiftRetVal<T> {
                 final T value;
                 final int bytesRead;
@@ -354,6 +396,174 @@ impl Renderer<(FunctionDefinition, dart::Tokens)> for TypeHelpersRenderer<'_> {
                 return uint8List;
             }
 
+            bool uniffiDeepEquals(Object? a, Object? b) {
+                if (identical(a, b)) {
+                    return true;
+                }
+                if (a is List && b is List) {
+                    if (a.length != b.length) {
+                        return false;
+                    }
+                    for (var i = 0; i < a.length; i++) {
+                        if (!uniffiDeepEquals(a[i], b[i])) {
+                            return false;
+                        }
+                    }
+                    return true;
+                }
+                if (a is Map && b is Map) {
+                    if (a.length != b.length) {
+                        return false;
+                    }
+                    for (final key in a.keys) {
+                        if (!b.containsKey(key) || !uniffiDeepEquals(a[key], b[key])) {
+                            return false;
+                        }
+                    }
+                    return true;
+                }
+                return a == b;
+            }
+
+            // Default of nullable `copyWith` parameters, so passing `null` can clear a field
+            const Object uniffiUnset = Object();
+
+            int uniffiDeepHash(Object? value) {
+                if (value is List) {
+                    return Object.hashAll(value.map(uniffiDeepHash));
+                }
+                if (value is Map) {
+                    return Object.hashAllUnordered(value.entries.map(
+                        (e) => Object.hash(uniffiDeepHash(e.key), uniffiDeepHash(e.value))));
+                }
+                return value.hashCode;
+            }
+
+            BigInt uniffiUInt64ToBigInt(int value) {
+                return BigInt.from(value).toUnsigned(64);
+            }
+
+            int uniffiBigIntToUInt64(BigInt value) {
+                if (value.isNegative || value.bitLength > 64) {
+                    throw ArgumentError("Value out of range for u64: " + value.toString());
+                }
+                return value.toSigned(64).toInt();
+            }
+
+            // Number of bytes `value` takes up as UTF-8, worked out without encoding it, so that
+            // sizing a buffer doesn't encode every string an extra time.
+            int uniffiUtf8Length(String value) {
+                var length = value.length;
+                for (var i = 0; i < value.length; i++) {
+                    final unit = value.codeUnitAt(i);
+                    if (unit < 0x80) {
+                        continue;
+                    } else if (unit < 0x800) {
+                        length += 1;
+                    } else if ((unit & 0xFC00) == 0xD800 && i + 1 < value.length && (value.codeUnitAt(i + 1) & 0xFC00) == 0xDC00) {
+                        // A surrogate pair is two code units and four bytes
+                        length += 2;
+                        i++;
+                    } else {
+                        length += 2;
+                    }
+                }
+                return length;
+            }
+
+            // UniFFI serializes numbers big-endian, while typed data lists hold them in host byte
+            // order. Converts `bytes` between the two in place, in elements of `width` bytes.
+            void uniffiSwapHostByteOrder(Uint8List bytes, int width) {
+                if (width == 1 || Endian.host == Endian.big) {
+                    return;
+                }
+                final data = ByteData.sublistView(bytes);
+                switch (width) {
+                    case 2:
+                        for (var i = 0; i < bytes.length; i += 2) {
+                            data.setUint16(i, data.getUint16(i, Endian.little));
+                        }
+                    case 4:
+                        for (var i = 0; i < bytes.length; i += 4) {
+                            data.setUint32(i, data.getUint32(i, Endian.little));
+                        }
+                    case 8:
+                        for (var i = 0; i < bytes.length; i += 8) {
+                            data.setUint64(i, data.getUint64(i, Endian.little));
+                        }
+                }
+            }
+
+            $(if self.config.zero_copy_bytes() {
+                // Frees the `RustBuffer` behind a collected view natively, even at isolate shutdown.
+                // Only libraries whose scaffolding came from `uniffi_dart::generate_scaffolding`
+                // provide it; without it, lifted bytes are copied instead.
+                final Pointer<NativeFinalizerFunction>? _uniffiRustBufferFinalizer =
+                    _UniffiLib._dylib.providesSymbol($(&rustbuffer_finalize))
+                        ? _UniffiLib._dylib.lookup<NativeFinalizerFunction>($(&rustbuffer_finalize))
+                        : null;
+
+                final _uniffiRustBufferToken = _UniffiLib._dylib.lookupFunction<
+                    Pointer<Void> Function(RustBuffer),
+                    Pointer<Void> Function(RustBuffer)>($(&rustbuffer_token));
+
+                // A view of `length` bytes at `offset` into `buf`, which takes ownership of `buf`
+                // and frees it once garbage collected.
+                Uint8List uniffiRustBufferView(RustBuffer buf, int offset, int length) {
+                    final finalizer = _uniffiRustBufferFinalizer;
+                    if (finalizer == null) {
+                        try {
+                            return Uint8List.fromList(Uint8List.sublistView(buf.asUint8List(), offset, offset + length));
+                        } finally {
+                            buf.free();
+                        }
+                    }
+                    final data = Pointer<Uint8>.fromAddress(buf.data.address + offset);
+                    return data.asTypedList(length, finalizer: finalizer, token: _uniffiRustBufferToken(buf));
+                }
+            })
+
+            $(if self.config.nested_optionals() {
+                // The value of an optional nested in another optional, where `null` alone can't
+                // tell `Some(None)` (`Some(null)`) from `None`.
+                sealed class Optional<T> {
+                    const Optional();
+                }
+
+                final class Some<T> extends Optional<T> {
+                    final T value;
+                    const Some(this.value);
+
+                    @override
+                    bool operator ==(Object other) {
+                        return other is Some && uniffiDeepEquals(value, other.value);
+                    }
+
+                    @override
+                    int get hashCode => Object.hash(Some, uniffiDeepHash(value));
+
+                    @override
+                    String toString() {
+                        return "Some(" + value.toString() + ")";
+                    }
+                }
+
+                final class None<T> extends Optional<T> {
+                    const None();
+
+                    @override
+                    bool operator ==(Object other) => other is None;
+
+                    @override
+                    int get hashCode => (None).hashCode;
+
+                    @override
+                    String toString() {
+                        return "None";
+                    }
+                }
+            })
+
             $(helpers_definitions)
 
             const int UNIFFI_RUST_FUTURE_POLL_READY = 0;
@@ -361,61 +571,121 @@ impl Renderer<(FunctionDefinition, dart::Tokens)> for TypeHelpersRenderer<'_> {
 
             typedef UniffiRustFutureContinuationCallback = Void Function(Uint64, Int8);
 
+            // Thrown by an async call whose Rust future was cancelled through its token.
+            class UniffiCancelledException implements Exception {
+                const UniffiCancelledException();
+
+                @override
+                String toString() => "UniffiCancelledException: the Rust future was cancelled";
+            }
+
+            // Cancels the Rust futures of every async call it was passed to that is still running,
+            // and of those started after it was cancelled.
+            class UniffiCancellationToken {
+                final _listeners = <void Function()>{};
+                bool _isCancelled = false;
+
+                bool get isCancelled => _isCancelled;
+
+                void cancel() {
+                    if (_isCancelled) {
+                        return;
+                    }
+                    _isCancelled = true;
+                    for (final listener in _listeners.toList()) {
+                        listener();
+                    }
+                    _listeners.clear();
+                }
+            }
+
+            // The poll results still awaited by async calls, keyed by the callback data they
+            // passed to `pollFunc`.
+            final _uniffiPendingPolls = UniffiHandleMap<Completer<int>>();
+
+            // A single continuation callback serves every async call. It only keeps the isolate
+            // alive while some poll is pending, like a per-call listener would.
+            final NativeCallable<UniffiRustFutureContinuationCallback> _uniffiRustFutureContinuation =
+                NativeCallable<UniffiRustFutureContinuationCallback>.listener(_uniffiOnRustFutureContinuation);
+
+            void _uniffiOnRustFutureContinuation(int handle, int pollResult) {
+                final completer = _uniffiPendingPolls.get(handle);
+                _uniffiPendingPolls.remove(handle);
+                if (_uniffiPendingPolls.isEmpty) {
+                    _uniffiRustFutureContinuation.keepIsolateAlive = false;
+                }
+                completer.complete(pollResult);
+            }
+
+            Future<int> _uniffiPollRustFuture(
+                Pointer<Void> rustFuture,
+                void Function(Pointer<Void>, Pointer<NativeFunction<UniffiRustFutureContinuationCallback>>, Pointer<Void>) pollFunc,
+            ) {
+                final completer = Completer<int>();
+                final handle = _uniffiPendingPolls.insert(completer);
+                _uniffiRustFutureContinuation.keepIsolateAlive = true;
+                pollFunc(rustFuture, _uniffiRustFutureContinuation.nativeFunction, Pointer<Void>.fromAddress(handle));
+                return completer.future;
+            }
+
             Future<T> uniffiRustCallAsync<T, F>(
                 Pointer<Void> Function() rustFutureFunc,
                 void Function(Pointer<Void>, Pointer<NativeFunction<UniffiRustFutureContinuationCallback>>, Pointer<Void>) pollFunc,
+                void Function(Pointer<Void>) cancelFunc,
                 F Function(Pointer<Void>, Pointer<RustCallStatus>) completeFunc,
                 void Function(Pointer<Void>) freeFunc,
                 T Function(F) liftFunc, [
                 UniffiRustCallStatusErrorHandler? errorHandler,
+                UniffiCancellationToken? cancellationToken,
+                Duration? timeout,
             ]) async {
                 final rustFuture = rustFutureFunc();
-                final completer = Completer<int>();
-
-                late final NativeCallable<UniffiRustFutureContinuationCallback> callback;
 
-                void poll() {
-                    pollFunc(
-                        rustFuture,
-                        callback.nativeFunction,
-                        Pointer<Void>.fromAddress(0),
-                    );
-                }
-                void onResponse(int _idx, int pollResult) {
-                    if (pollResult == UNIFFI_RUST_FUTURE_POLL_READY) {
-                        completer.complete(pollResult);
-                    } else {
-                        poll();
-                    }
-                }
-                callback = NativeCallable<UniffiRustFutureContinuationCallback>.listener(onResponse);
+                // Cancelling wakes the pending poll with a ready result, after which
+                // `completeFunc` reports `CALL_CANCELLED`.
+                void cancel() => cancelFunc(rustFuture);
+                var timedOut = false;
+                final timer = timeout == null ? null : Timer(timeout, () {
+                    timedOut = true;
+                    cancel();
+                });
+                cancellationToken?._listeners.add(cancel);
 
                 try {
-                    poll();
-                    await completer.future;
-                    callback.close();
-
+                    if (cancellationToken?.isCancelled ?? false) {
+                        cancel();
+                    }
+                    while (await _uniffiPollRustFuture(rustFuture, pollFunc) != UNIFFI_RUST_FUTURE_POLL_READY) {}
 
+                    // Errors and panics from the future surface through the status of
+                    // `completeFunc`, so they are checked before the result is lifted.
                     final status = calloc<RustCallStatus>();
                     try {
-
                         final result = completeFunc(rustFuture, status);
-
+                        if (status.ref.code == CALL_CANCELLED && timedOut) {
+                            throw TimeoutException("The Rust future did not complete in time", timeout);
+                        }
+                        checkCallStatus(errorHandler ?? NullRustCallStatusErrorHandler(), status);
                         return liftFunc(result);
                     } finally {
                         calloc.free(status);
                     }
                 } finally {
+                    timer?.cancel();
+                    cancellationToken?._listeners.remove(cancel);
                     freeFunc(rustFuture);
                 }
             }
 
+            // Handles are always odd, which is how Rust tells objects implemented in Dart apart
+            // from its own.
             class UniffiHandleMap<T> {
                 final Map<int, T> _map = {};
-                int _counter = 0;
+                int _counter = 1;
 
                 int insert(T obj) {
-                final handle = _counter++;
+                final handle = _counter;
+                _counter += 2;
                 _map[handle] = obj;
                 return handle;
                 }
@@ -435,15 +705,19 @@ impl Renderer<(FunctionDefinition, dart::Tokens)> for TypeHelpersRenderer<'_> {
                         UniffiInternalError.unexpectedStaleHandle, "Handle not found");
                 }
                 }
+
+                bool get isEmpty => _map.isEmpty;
             }
 
+            $(callback_interface::generate_foreign_future_definitions(self.ci))
+
         };
 
         (types_helper_code, function_definitions)
     }
 }
 
-pub fn generate_type(ty: &Type) -> dart::Tokens {
+pub fn generate_type(ty: &Type, config: &Config) -> dart::Tokens {
     match ty {
         Type::UInt8
         | Type::UInt32
@@ -451,20 +725,27 @@ pub fn generate_type(ty: &Type) -> dart::Tokens {
         | Type::Int16
         | Type::Int64
         | Type::UInt16
-        | Type::Int32
-        | Type::UInt64 => quote!(int),
+        | Type::Int32 => quote!(int),
+        Type::UInt64 => quote!($(DartCodeOracle::find(ty).type_label(config))),
         Type::Float32 | Type::Float64 => quote!(double),
         Type::String => quote!(String),
         Type::Bytes => quote!(Uint8List),
         Type::Object { name, .. } => quote!($name),
         Type::Boolean => quote!(bool),
-        Type::Optional { inner_type } => quote!($(generate_type(inner_type))?),
-        Type::Sequence { inner_type } => quote!(List<$(generate_type(inner_type))>),
+        Type::Optional { inner_type } if compounds::is_nested_optional(ty, config) => {
+            quote!(Optional<$(generate_type(inner_type, config))>)
+        }
+        Type::Optional { inner_type } => quote!($(generate_type(inner_type, config))?),
+        Type::Sequence { .. } if compounds::typed_data_list(ty, config).is_some() => {
+            quote!($(DartCodeOracle::find(ty).type_label(config)))
+        }
+        Type::Sequence { inner_type } => quote!(List<$(generate_type(inner_type, config))>),
         Type::Map {
             key_type,
             value_type,
-        } => quote!(Map<$(generate_type(key_type)), $(generate_type(value_type))>),
+        } => quote!(Map<$(generate_type(key_type, config)), $(generate_type(value_type, config))>),
         Type::Enum { name, .. } => quote!($(DartCodeOracle::class_name(name))),
+        Type::Timestamp => quote!(DateTime),
         Type::Duration => quote!(Duration),
         Type::Record { name, .. } => quote!($name),
         Type::Custom { name, .. } => quote!($name),
@@ -25,6 +25,8 @@ pub struct TestConfig {
     pub no_delete: bool,
     /// Delay in seconds after test failure (0 = no delay; None = default)
     pub failure_delay_secs: Option<u64>,
+    /// Generate the bindings from the compiled library instead of the UDL file
+    pub library_mode: bool,
 }
 
 impl TestConfig {
@@ -64,6 +66,11 @@ impl TestConfig {
         self.failure_delay_secs = Some(delay_secs);
         self
     }
+
+    pub fn with_library_mode(mut self, library_mode: bool) -> Self {
+        self.library_mode = library_mode;
+        self
+    }
 }
 
 /// Run a test with default options (env vars honored)
@@ -160,6 +167,7 @@ fn run_test_impl(
     environment:
       sdk: '>=3.5.0'
     dev_dependencies:
+      analyzer: '>=6.0.0 <8.0.0'
       test: ^1.24.3
     dependencies:
       ffi: ^2.0.1
@@ -175,7 +183,7 @@ fn run_test_impl(
         config_path.as_deref(),
         Some(&out_dir),
         &test_helper.cdylib_path()?,
-        false,
+        test_config.library_mode,
     )?;
 
     // Copy fixture test files to output directory